@@ -1,5 +1,5 @@
 use crate::*;
-use near_sdk::{assert_one_yocto, ext_contract};
+use near_sdk::{assert_one_yocto, ext_contract, json_types::U64};
 pub trait NonFungibleTokenCore {
     fn nft_approve(&mut self, token_id: TokenId, account_id: AccountId, msg: Option<String>);
     fn nft_is_approved(
@@ -39,44 +39,19 @@ trait ExtContract {
         //the maximum amount of accounts the market can payout at once (this is limited by GAS)
         max_len_payout: u32,
     );
+    fn nft_is_approved(
+        &self,
+        token_id: TokenId,
+        approved_account_id: AccountId,
+        approval_id: Option<u64>,
+    ) -> bool;
 }
 
 #[near_bindgen]
 impl NonFungibleTokenCore for Contract {
     #[payable]
     fn nft_approve(&mut self, token_id: TokenId, owner_id: AccountId, msg: Option<String>) {
-        assert_at_least_one_yocto();
-        let mut token = self.token_by_id.get(&token_id).expect("No token");
-
-        assert_eq!(
-            &env::predecessor_account_id(),
-            &token.owner_id,
-            "You are not the token Owner."
-        );
-
-        let approval_id: u64 = token.next_approval_id;
-
-        let is_new_approval = token
-            .approved_account_ids
-            .insert(owner_id.clone(), approval_id)
-            .is_none();
-
-        let storage_used = if is_new_approval {
-            bytes_for_approved_account_id(&owner_id)
-        } else {
-            0
-        };
-
-        token.next_approval_id += 1;
-        self.token_by_id.insert(&token_id, &token);
-
-        refund_deposit(storage_used);
-
-        if let Some(msg) = msg {
-            ext_non_fungible_approval_receiver::ext(owner_id)
-                .nft_on_approve(token_id, token.owner_id, approval_id, msg)
-                .as_return();
-        }
+        self.internal_approve(token_id, owner_id, None, msg);
     }
 
     fn nft_is_approved(
@@ -87,6 +62,11 @@ impl NonFungibleTokenCore for Contract {
     ) -> bool {
         let token = self.token_by_id.get(&token_id).expect("No token");
 
+        //an expired approval is no longer valid, even before it's pruned
+        if token.is_approval_expired(&account_id, env::block_timestamp_ms()) {
+            return false;
+        }
+
         let approval = token.approved_account_ids.get(&account_id);
 
         if let Some(approval) = approval {
@@ -109,7 +89,17 @@ impl NonFungibleTokenCore for Contract {
         assert_eq!(&predecessor_account_id, &token.owner_id);
 
         if token.approved_account_ids.remove(&account_id).is_some() {
-            refund_approved_account_ids_iter(predecessor_account_id, [account_id].iter());
+            //revoking the marketplace invalidates the listing it holds for the token
+            if account_id == env::current_account_id() {
+                self.internal_remove_native_sale(&token_id);
+            }
+            let expiry = token.approval_expires_at.remove(&account_id);
+            refund_approved_account_ids_iter(
+                predecessor_account_id,
+                [account_id.clone()]
+                    .iter()
+                    .chain(expiry.map(|_| account_id).iter()),
+            );
 
             self.token_by_id.insert(&token_id, &token);
         }
@@ -124,13 +114,132 @@ impl NonFungibleTokenCore for Contract {
         assert_eq!(&predecessor_account_id, &token.owner_id);
 
         if !token.approved_account_ids.is_empty() {
-            refund_approved_account_ids(predecessor_account_id, &token.approved_account_ids);
+            if token
+                .approved_account_ids
+                .contains_key(&env::current_account_id())
+            {
+                self.internal_remove_native_sale(&token_id);
+            }
+            refund_token_approvals(predecessor_account_id, &token);
             token.approved_account_ids.clear();
+            token.approval_expires_at.clear();
             self.token_by_id.insert(&token_id, &token);
         }
     }
 }
 
+#[near_bindgen]
+impl Contract {
+    /// Approve an account until `expires_at` (unix epoch in milliseconds). After that the approval
+    /// can't be used anymore and anyone can prune it with `nft_prune_expired_approvals`.
+    #[payable]
+    pub fn nft_approve_until(
+        &mut self,
+        token_id: TokenId,
+        account_id: AccountId,
+        expires_at: U64,
+        msg: Option<String>,
+    ) {
+        require!(
+            expires_at.0 > env::block_timestamp_ms(),
+            "expires_at must be in the future"
+        );
+        self.internal_approve(token_id, account_id, Some(expires_at.0), msg);
+    }
+
+    /// Remove the expired approvals of a token and refund their storage to the owner.
+    /// Returns the accounts whose approval was removed.
+    pub fn nft_prune_expired_approvals(&mut self, token_id: TokenId) -> Vec<AccountId> {
+        let mut token = self.token_by_id.get(&token_id).expect("No token");
+        let now_ms = env::block_timestamp_ms();
+
+        let expired: Vec<AccountId> = token
+            .approval_expires_at
+            .iter()
+            .filter(|(_, expires_at)| now_ms >= **expires_at)
+            .map(|(account_id, _)| account_id.clone())
+            .collect();
+        if expired.is_empty() {
+            return expired;
+        }
+
+        for account_id in expired.iter() {
+            token.approved_account_ids.remove(account_id);
+            token.approval_expires_at.remove(account_id);
+            //an expired marketplace approval means the listing can't be bought anymore
+            if account_id == &env::current_account_id() {
+                self.internal_remove_native_sale(&token_id);
+            }
+        }
+        self.token_by_id.insert(&token_id, &token);
+
+        //both the approval and its expiry are refunded
+        refund_approved_account_ids_iter(token.owner_id, expired.iter().chain(expired.iter()));
+
+        expired
+    }
+}
+
+impl Contract {
+    //approve an account on a token, optionally until a given time. Used by nft_approve and nft_approve_until
+    pub(crate) fn internal_approve(
+        &mut self,
+        token_id: TokenId,
+        owner_id: AccountId,
+        expires_at: Option<u64>,
+        msg: Option<String>,
+    ) {
+        assert_at_least_one_yocto();
+        let mut token = self.token_by_id.get(&token_id).expect("No token");
+
+        assert_eq!(
+            &env::predecessor_account_id(),
+            &token.owner_id,
+            "You are not the token Owner."
+        );
+        self.internal_assert_transferable(&token_id, &token);
+
+        let approval_id: u64 = token.next_approval_id;
+
+        let is_new_approval = token
+            .approved_account_ids
+            .insert(owner_id.clone(), approval_id)
+            .is_none();
+
+        let mut storage_used = if is_new_approval {
+            bytes_for_approved_account_id(&owner_id)
+        } else {
+            0
+        };
+
+        //re-approving replaces any earlier expiry
+        let had_expiry = if let Some(expires_at) = expires_at {
+            token
+                .approval_expires_at
+                .insert(owner_id.clone(), expires_at)
+                .is_some()
+        } else {
+            token.approval_expires_at.remove(&owner_id).is_some()
+        };
+        if expires_at.is_some() && !had_expiry {
+            storage_used += bytes_for_approved_account_id(&owner_id);
+        } else if expires_at.is_none() && had_expiry {
+            refund_approved_account_ids_iter(token.owner_id.clone(), [owner_id.clone()].iter());
+        }
+
+        token.next_approval_id += 1;
+        self.token_by_id.insert(&token_id, &token);
+
+        refund_deposit(storage_used);
+
+        if let Some(msg) = msg {
+            ext_non_fungible_approval_receiver::ext(owner_id)
+                .nft_on_approve(token_id, token.owner_id, approval_id, msg)
+                .as_return();
+        }
+    }
+}
+
 #[near_bindgen]
 impl NonFungibleApprovalReceiver for Contract {
     /// where we add the sale because we know nft owner can only call nft_approve
@@ -156,6 +265,17 @@ impl NonFungibleApprovalReceiver for Contract {
         //make sure the owner ID is the signer.
         assert_eq!(owner_id, signer_id, "owner_id should be signer_id");
 
+        //soulbound tokens minted on this contract can never be listed
+        if nft_contract_id == env::current_account_id() {
+            if let Some(token) = self.token_by_id.get(&token_id) {
+                self.internal_assert_transferable(&token_id, &token);
+                assert!(
+                    self.token_parent.get(&token_id).is_none(),
+                    "Nested tokens can't be listed"
+                );
+            }
+        }
+
         //we need to enforce that the user has enough storage for 1 EXTRA sale.
 
         //get the storage for a sale. dot 0 converts from U128 to u128
@@ -185,7 +305,7 @@ impl NonFungibleApprovalReceiver for Contract {
         let contract_and_token_id = format!("{}{}{}", nft_contract_id, DELIMETER, token_id);
 
         //insert the key value pair into the sales map. Key is the unique ID. value is the sale object
-        self.sales.insert(
+        let previous_sale = self.sales.insert(
             &contract_and_token_id,
             &Sale {
                 owner_id: owner_id.clone(),                   //owner of the sale / token
@@ -235,5 +355,14 @@ impl NonFungibleApprovalReceiver for Contract {
         //insert the set back into the collection for the given nft contract ID
         self.by_nft_contract_id
             .insert(&nft_contract_id, &by_nft_contract_id);
+
+        //keep the series stats of tokens minted on this contract up to date. The listing is covered by
+        //the storage reserved for the sale
+        if nft_contract_id == env::current_account_id() {
+            if let Some(previous_sale) = previous_sale {
+                self.internal_stats_remove_listing(&token_id, previous_sale.sale_conditions.0);
+            }
+            self.internal_stats_add_listing(&token_id, sale_conditions.0);
+        }
     }
 }
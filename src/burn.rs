@@ -0,0 +1,111 @@
+use near_sdk::assert_one_yocto;
+
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Burn a token. The owner can always burn, approved accounts only if the series allows it.
+    /// The storage freed by the token is refunded to the owner.
+    #[payable]
+    pub fn nft_burn(&mut self, token_id: TokenId, approval_id: Option<u64>, memo: Option<String>) {
+        assert_one_yocto();
+
+        let sender_id = env::predecessor_account_id();
+        let token = self.token_by_id.get(&token_id).expect("No token");
+
+        // Default the authorized ID to be None for the logs.
+        let mut authorized_id = None;
+        if sender_id != token.owner_id {
+            let series = self
+                .series_by_id
+                .get(&token.series_id)
+                .expect("Not a series");
+            require!(series.approved_can_burn, "Unauthorized");
+
+            let actual_approval_id = token
+                .approved_account_ids
+                .get(&sender_id)
+                .expect("Unauthorized");
+            require!(
+                !token.is_approval_expired(&sender_id, env::block_timestamp_ms()),
+                "Approval has expired"
+            );
+            if let Some(enforced_approval_id) = approval_id {
+                assert_eq!(
+                    actual_approval_id, &enforced_approval_id,
+                    "The actual approval_id {} is different from the given approval_id {}",
+                    actual_approval_id, enforced_approval_id,
+                );
+            }
+            authorized_id = Some(sender_id.to_string());
+        }
+
+        let (token, initial_storage_usage) = self.internal_burn(&token_id);
+
+        // Construct the burn log as per the events standard.
+        let nft_burn_log: EventLog = EventLog {
+            // Standard name ("nep171").
+            standard: NFT_STANDARD_NAME.to_string(),
+            // Version of the standard ("nft-1.0.0").
+            version: NFT_METADATA_SPEC.to_string(),
+            // The data related with the event stored in a vector.
+            event: EventLogVariant::NftBurn(vec![NftBurnLog {
+                // Owner of the burned token.
+                owner_id: token.owner_id.to_string(),
+                // The optional authorized account ID that burned the token on behalf of the owner.
+                authorized_id,
+                // A vector containing the token IDs as strings.
+                token_ids: vec![token_id],
+                // An optional memo to include.
+                memo,
+            }]),
+        };
+
+        // Log the serialized json.
+        env::log_str(&nft_burn_log.to_string());
+
+        //refund the storage that was freed to the owner of the token
+        refund_freed_storage(token.owner_id, initial_storage_usage);
+    }
+
+    /// Revoke a soulbound token. Only the series owner can do this; the token is burned
+    /// and the freed storage is refunded to its holder.
+    #[payable]
+    pub fn nft_revoke_token(&mut self, token_id: TokenId, memo: Option<String>) {
+        assert_one_yocto();
+
+        let sender_id = env::predecessor_account_id();
+        let token = self.token_by_id.get(&token_id).expect("No token");
+        let series = self
+            .series_by_id
+            .get(&token.series_id)
+            .expect("Not a series");
+        require!(
+            sender_id == series.owner_id,
+            "Only the series owner can revoke tokens"
+        );
+        require!(series.soulbound, "Only soulbound tokens can be revoked");
+
+        let (token, initial_storage_usage) = self.internal_burn(&token_id);
+
+        let nft_burn_log: EventLog = EventLog {
+            // Standard name ("nep171").
+            standard: NFT_STANDARD_NAME.to_string(),
+            // Version of the standard ("nft-1.0.0").
+            version: NFT_METADATA_SPEC.to_string(),
+            // The series owner is logged as the authorized account that burned the token.
+            event: EventLogVariant::NftBurn(vec![NftBurnLog {
+                owner_id: token.owner_id.to_string(),
+                authorized_id: Some(sender_id.to_string()),
+                token_ids: vec![token_id],
+                memo,
+            }]),
+        };
+
+        // Log the serialized json.
+        env::log_str(&nft_burn_log.to_string());
+
+        //refund the storage that was freed to the holder of the token
+        refund_freed_storage(token.owner_id, initial_storage_usage);
+    }
+}
@@ -0,0 +1,232 @@
+use near_sdk::assert_one_yocto;
+
+use crate::{nft_core::NonFungibleTokenCore, *};
+
+//the maximum number of tokens that can be nested directly in one token
+const MAX_CHILDREN: u64 = 10;
+//the maximum number of levels of nesting, counting the root token (this is limited by GAS when moving a root)
+const MAX_NESTING_DEPTH: u64 = 3;
+
+#[near_bindgen]
+impl Contract {
+    /// Nest a token inside another token from this contract. Both tokens have to be owned by the caller.
+    /// While nested, the token can't be transferred or listed on its own and moves along with its parent.
+    #[payable]
+    pub fn nft_nest(&mut self, token_id: TokenId, parent_token_id: TokenId) {
+        assert_at_least_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let owner_id = env::predecessor_account_id();
+        let token = self.token_by_id.get(&token_id).expect("No token");
+        let parent = self
+            .token_by_id
+            .get(&parent_token_id)
+            .expect("No parent token");
+        require!(
+            token.owner_id == owner_id && parent.owner_id == owner_id,
+            "Must own both tokens"
+        );
+        require!(token_id != parent_token_id, "A token can't own itself");
+        require!(
+            self.token_parent.get(&token_id).is_none(),
+            "Token is already nested"
+        );
+        self.internal_assert_transferable(&token_id, &token);
+
+        //make sure we don't create a cycle and stay within the nesting depth. The depth counts the
+        //parent's ancestors, the parent, the token itself and everything nested below it
+        let ancestors = self.internal_ancestors(&parent_token_id);
+        require!(
+            !ancestors.contains(&token_id),
+            "A token can't be nested inside one of its own children"
+        );
+        require!(
+            ancestors.len() as u64 + 2 + self.internal_subtree_height(&token_id)
+                <= MAX_NESTING_DEPTH,
+            "Maximum nesting depth reached"
+        );
+
+        let mut children = self
+            .token_children
+            .get(&parent_token_id)
+            .unwrap_or_else(|| {
+                UnorderedSet::new(
+                    StorageKey::TokenChildrenInner {
+                        token_id_hash: hash_token_id(&parent_token_id),
+                    }
+                    .try_to_vec()
+                    .unwrap(),
+                )
+            });
+        require!(
+            children.len() < MAX_CHILDREN,
+            "Maximum number of nested tokens reached"
+        );
+        children.insert(&token_id);
+        self.token_children.insert(&parent_token_id, &children);
+        self.token_parent.insert(&token_id, &parent_token_id);
+
+        //a nested token can't be bought on its own anymore
+        self.internal_remove_native_sale(&token_id);
+
+        let required_storage_in_bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+        refund_deposit(required_storage_in_bytes);
+    }
+
+    /// Take a nested token out of its parent. It stays with the current owner and the freed storage is refunded.
+    #[payable]
+    pub fn nft_unnest(&mut self, token_id: TokenId) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let owner_id = env::predecessor_account_id();
+        let token = self.token_by_id.get(&token_id).expect("No token");
+        require!(token.owner_id == owner_id, "Must own the token");
+
+        let parent_token_id = self
+            .token_parent
+            .remove(&token_id)
+            .expect("Token is not nested");
+        let mut children = self.token_children.get(&parent_token_id).unwrap();
+        children.remove(&token_id);
+        if children.is_empty() {
+            self.token_children.remove(&parent_token_id);
+        } else {
+            self.token_children.insert(&parent_token_id, &children);
+        }
+
+        refund_freed_storage(owner_id, initial_storage_usage);
+    }
+
+    //get the parent of a nested token
+    pub fn nft_parent(&self, token_id: TokenId) -> Option<TokenId> {
+        self.token_parent.get(&token_id)
+    }
+
+    //paginate through the tokens nested directly inside a token
+    pub fn nft_children(
+        &self,
+        token_id: TokenId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonToken> {
+        let children = if let Some(children) = self.token_children.get(&token_id) {
+            children
+        } else {
+            return vec![];
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        children
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|token_id| self.nft_token(token_id).unwrap())
+            .collect()
+    }
+
+    //get the owner of the top level token a token is nested in (or of the token itself when it isn't nested)
+    pub fn nft_root_owner(&self, token_id: TokenId) -> Option<AccountId> {
+        let root_token_id = self.internal_ancestors(&token_id).pop().unwrap_or(token_id);
+        self.token_by_id
+            .get(&root_token_id)
+            .map(|token| token.owner_id)
+    }
+}
+
+impl Contract {
+    //get the parents of a token, closest first and the root last
+    pub(crate) fn internal_ancestors(&self, token_id: &TokenId) -> Vec<TokenId> {
+        let mut ancestors = Vec::new();
+        let mut current = token_id.clone();
+        while let Some(parent_token_id) = self.token_parent.get(&current) {
+            ancestors.push(parent_token_id.clone());
+            current = parent_token_id;
+        }
+        ancestors
+    }
+
+    //how many levels of tokens are nested below a token
+    pub(crate) fn internal_subtree_height(&self, token_id: &TokenId) -> u64 {
+        self.token_children.get(token_id).map_or(0, |children| {
+            children
+                .iter()
+                .map(|child_id| 1 + self.internal_subtree_height(&child_id))
+                .max()
+                .unwrap_or(0)
+        })
+    }
+
+    //how many tokens are nested below a token, at any level
+    pub(crate) fn internal_descendant_count(&self, token_id: &TokenId) -> u64 {
+        self.token_children.get(token_id).map_or(0, |children| {
+            children
+                .iter()
+                .map(|child_id| 1 + self.internal_descendant_count(&child_id))
+                .sum()
+        })
+    }
+
+    //move every token nested below a token to the new owner. Returns the IDs of the tokens that moved
+    pub(crate) fn internal_move_children(
+        &mut self,
+        token_id: &TokenId,
+        old_owner_id: &AccountId,
+        new_owner_id: &AccountId,
+        payer_id: Option<&AccountId>,
+    ) -> Vec<TokenId> {
+        let mut moved = Vec::new();
+        let mut pending = vec![token_id.clone()];
+
+        while let Some(parent_token_id) = pending.pop() {
+            let children = if let Some(children) = self.token_children.get(&parent_token_id) {
+                children
+            } else {
+                continue;
+            };
+
+            for child_id in children.iter() {
+                let mut child = self.token_by_id.get(&child_id).expect("No token");
+                self.internal_assert_transferable(&child_id, &child);
+
+                self.internal_remove_native_sale(&child_id);
+                self.internal_change_token_owner(&child_id, old_owner_id, new_owner_id, payer_id);
+
+                if !child.approved_account_ids.is_empty() {
+                    refund_token_approvals(old_owner_id.clone(), &child);
+                }
+                child.owner_id = new_owner_id.clone();
+                child.approved_account_ids = Default::default();
+                child.approval_expires_at = Default::default();
+                child.next_approval_id = 0;
+                self.token_by_id.insert(&child_id, &child);
+
+                pending.push(child_id.clone());
+                moved.push(child_id);
+            }
+        }
+
+        moved
+    }
+
+    //remove a token from its parent and release the tokens nested in it, used when the token is burned
+    pub(crate) fn internal_detach_nesting(&mut self, token_id: &TokenId) {
+        if let Some(parent_token_id) = self.token_parent.remove(token_id) {
+            let mut children = self.token_children.get(&parent_token_id).unwrap();
+            children.remove(token_id);
+            if children.is_empty() {
+                self.token_children.remove(&parent_token_id);
+            } else {
+                self.token_children.insert(&parent_token_id, &children);
+            }
+        }
+
+        if let Some(mut children) = self.token_children.remove(token_id) {
+            for child_id in children.iter() {
+                self.token_parent.remove(&child_id);
+            }
+            children.clear();
+        }
+    }
+}
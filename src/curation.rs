@@ -0,0 +1,149 @@
+use near_sdk::assert_one_yocto;
+
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Switch between open creation, where anyone can create a series, and curated creation, where only
+    /// approved creators and the contract owner can. Only the contract owner can do this.
+    #[payable]
+    pub fn set_curated_creation(&mut self, curated: bool) {
+        assert_one_yocto();
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only the contract owner can change the creation mode"
+        );
+        self.curated_creation = curated;
+    }
+
+    /// Allow an account to create series while creation is curated. Only the contract owner can do this.
+    #[payable]
+    pub fn add_approved_creator(&mut self, account_id: AccountId) {
+        assert_at_least_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only the contract owner can approve creators"
+        );
+        require!(
+            self.approved_creators.insert(&account_id),
+            "Already an approved creator"
+        );
+
+        let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
+        refund_deposit(required_storage_in_bytes);
+    }
+
+    /// Remove an approved creator. Series it already created are kept.
+    #[payable]
+    pub fn remove_approved_creator(&mut self, account_id: AccountId) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only the contract owner can remove creators"
+        );
+        require!(
+            self.approved_creators.remove(&account_id),
+            "Not an approved creator"
+        );
+
+        refund_freed_storage(self.owner_id.clone(), initial_storage_usage);
+    }
+
+    /// Allow an account to mark series as verified. Only the contract owner can do this.
+    #[payable]
+    pub fn add_curator(&mut self, account_id: AccountId) {
+        assert_at_least_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only the contract owner can add curators"
+        );
+        require!(self.curators.insert(&account_id), "Already a curator");
+
+        let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
+        refund_deposit(required_storage_in_bytes);
+    }
+
+    /// Remove a curator. Series it verified stay verified.
+    #[payable]
+    pub fn remove_curator(&mut self, account_id: AccountId) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only the contract owner can remove curators"
+        );
+        require!(self.curators.remove(&account_id), "Not a curator");
+
+        refund_freed_storage(self.owner_id.clone(), initial_storage_usage);
+    }
+
+    /// Mark a series as verified or take the mark away. Only curators and the contract owner can do this.
+    #[payable]
+    pub fn set_series_verified(&mut self, id: u64, verified: bool) {
+        assert_at_least_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let caller = env::predecessor_account_id();
+        require!(
+            caller == self.owner_id || self.curators.contains(&caller),
+            "Only curators can verify series"
+        );
+
+        let mut series = self.series_by_id.get(&id).expect("Not a series");
+        series.verified = verified;
+        self.series_by_id.insert(&id, &series);
+        if verified {
+            self.verified_series.insert(&id);
+        } else {
+            self.verified_series.remove(&id);
+        }
+
+        //verifying takes storage for the index, removing the mark frees it again
+        let required_storage_in_bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+        refund_deposit(required_storage_in_bytes);
+        refund_freed_storage(caller, initial_storage_usage);
+    }
+
+    //whether only approved creators can create series
+    pub fn is_curated_creation(&self) -> bool {
+        self.curated_creation
+    }
+
+    //whether an account is on the approved creators list
+    pub fn is_approved_creator(&self, account_id: AccountId) -> bool {
+        self.approved_creators.contains(&account_id)
+    }
+
+    //paginate through the approved creators
+    pub fn get_approved_creators(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<AccountId> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.approved_creators
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+
+    //paginate through the curators
+    pub fn get_curators(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<AccountId> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.curators
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+}
@@ -0,0 +1,80 @@
+use crate::*;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CreatorEarnings {
+    // Total royalties received from secondary sales
+    pub royalty_income: U128,
+    // Total received by the series owner from selling their own tokens
+    pub primary_sale_income: U128,
+}
+
+impl Default for CreatorEarnings {
+    fn default() -> Self {
+        Self {
+            royalty_income: U128(0),
+            primary_sale_income: U128(0),
+        }
+    }
+}
+
+impl CreatorEarnings {
+    fn add(&mut self, amount: Balance, is_royalty: bool) {
+        if is_royalty {
+            self.royalty_income = U128(self.royalty_income.0 + amount);
+        } else {
+            self.primary_sale_income = U128(self.primary_sale_income.0 + amount);
+        }
+    }
+}
+
+impl Contract {
+    //record the creator income of a purchase. The seller's share only counts when the seller is the series owner
+    pub(crate) fn internal_record_earnings(
+        &mut self,
+        series_id: Option<SeriesId>,
+        seller_id: &AccountId,
+        payout: &HashMap<AccountId, U128>,
+    ) {
+        let series_owner_id = series_id
+            .and_then(|id| self.series_by_id.get(&id))
+            .map(|series| series.owner_id);
+
+        let mut series_earnings =
+            series_id.map(|id| self.earnings_by_series.get(&id).unwrap_or_default());
+
+        for (account_id, amount) in payout.iter() {
+            let is_royalty = account_id != seller_id;
+            if !is_royalty && series_owner_id.as_ref() != Some(seller_id) {
+                continue;
+            }
+
+            let mut earnings = self.earnings_by_account.get(account_id).unwrap_or_default();
+            earnings.add(amount.0, is_royalty);
+            self.earnings_by_account.insert(account_id, &earnings);
+
+            if let Some(series_earnings) = series_earnings.as_mut() {
+                series_earnings.add(amount.0, is_royalty);
+            }
+        }
+
+        if let (Some(id), Some(series_earnings)) = (series_id, series_earnings) {
+            self.earnings_by_series.insert(&id, &series_earnings);
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    //get the total royalty and primary sale income of an account
+    pub fn get_creator_earnings(&self, account_id: AccountId) -> CreatorEarnings {
+        self.earnings_by_account
+            .get(&account_id)
+            .unwrap_or_default()
+    }
+
+    //get the total royalty and primary sale income paid out for a series
+    pub fn get_series_earnings(&self, series_id: SeriesId) -> CreatorEarnings {
+        self.earnings_by_series.get(&series_id).unwrap_or_default()
+    }
+}
@@ -3,7 +3,8 @@ use std::fmt;
 use near_sdk::serde::{Deserialize, Serialize};
 
 /// Enum that represents the data type of the EventLog.
-/// The enum can either be an NftMint or an NftTransfer.
+/// The enum can either be an NftMint, an NftTransfer, an NftBurn, an NftMetadataUpdate,
+/// a ContractMetadataUpdate, an NftRoyaltyUpdate or a SeriesTransfer.
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "event", content = "data")]
 #[serde(rename_all = "snake_case")]
@@ -12,6 +13,11 @@ use near_sdk::serde::{Deserialize, Serialize};
 pub enum EventLogVariant {
     NftMint(Vec<NftMintLog>),
     NftTransfer(Vec<NftTransferLog>),
+    NftBurn(Vec<NftBurnLog>),
+    NftMetadataUpdate(Vec<NftMetadataUpdateLog>),
+    ContractMetadataUpdate(Vec<ContractMetadataUpdateLog>),
+    NftRoyaltyUpdate(Vec<NftRoyaltyUpdateLog>),
+    SeriesTransfer(Vec<SeriesTransferLog>),
 }
 
 /// Interface to capture data about an event
@@ -77,3 +83,87 @@ pub struct NftTransferLog {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub memo: Option<String>,
 }
+
+/// An event log to capture token burning
+///
+/// Arguments
+/// * `owner_id`: owner of the burned tokens
+/// * `authorized_id`: approved account that burned the tokens on behalf of the owner
+/// * `token_ids`: ["1", "abc"]
+/// * `memo`: optional message
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftBurnLog {
+    pub owner_id: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorized_id: Option<String>,
+
+    pub token_ids: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+/// An event log to capture token metadata updates
+///
+/// Arguments
+/// * `token_ids`: ["1", "abc"]
+/// * `memo`: optional message
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftMetadataUpdateLog {
+    pub token_ids: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+/// An event log to capture contract metadata updates
+///
+/// Arguments
+/// * `memo`: optional message
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractMetadataUpdateLog {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+/// An event log to capture a change of royalty recipient on a series
+///
+/// Arguments
+/// * `series_id`: "1"
+/// * `old_account_id`: "creator.near"
+/// * `new_account_id`: "payout.near"
+/// * `max_percentage`: optional cap on the percentage paid to the new account
+/// * `token_ids`: tokens whose stored royalty was rewritten, omitted when the change applies to the whole series
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftRoyaltyUpdateLog {
+    pub series_id: String,
+    pub old_account_id: String,
+    pub new_account_id: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_percentage: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_ids: Option<Vec<String>>,
+}
+
+/// An event log to capture a series changing owner
+///
+/// Arguments
+/// * `series_id`: "1"
+/// * `old_owner_id`: "creator.near"
+/// * `new_owner_id`: "buyer.near"
+/// * `royalties_transferred`: whether the old owner's royalties now go to the new owner
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SeriesTransferLog {
+    pub series_id: String,
+    pub old_owner_id: String,
+    pub new_owner_id: String,
+    pub royalties_transferred: bool,
+}
@@ -6,6 +6,47 @@ pub(crate) fn royalty_to_payout(percentage: u32, amount_to_pay: Balance) -> U128
     U128((percentage as u128 * amount_to_pay) / 10_000u128)
 }
 
+//apply the series royalty redirects to a token's royalty. Percentages are only ever capped, never raised.
+pub(crate) fn apply_royalty_redirects(
+    royalty: &HashMap<AccountId, u32>,
+    redirects: &HashMap<AccountId, RoyaltyRedirect>,
+) -> HashMap<AccountId, u32> {
+    let mut result = HashMap::new();
+    for (account_id, percentage) in royalty.iter() {
+        let (receiver_id, amount) = match redirects.get(account_id) {
+            Some(redirect) => (
+                redirect.account_id.clone(),
+                redirect
+                    .max_percentage
+                    .map_or(*percentage, |max| max.min(*percentage)),
+            ),
+            None => (account_id.clone(), *percentage),
+        };
+        *result.entry(receiver_id).or_insert(0) += amount;
+    }
+    result
+}
+
+//build the royalty for newly minted tokens from the perpetual royalties passed in
+pub(crate) fn internal_mint_royalty(
+    perpetual_royalties: Option<HashMap<AccountId, u32>>,
+) -> HashMap<AccountId, u32> {
+    //Change this when listing
+    let mut royalty = HashMap::new();
+
+    if let Some(perpetual_royalties) = perpetual_royalties {
+        assert!(
+            perpetual_royalties.len() < 7,
+            "Cannot add more than 6 perpetual royalty amounts"
+        );
+        for (account, amount) in perpetual_royalties {
+            royalty.insert(account, amount);
+        }
+    }
+
+    royalty
+}
+
 pub(crate) fn bytes_for_approved_account_id(account_id: &AccountId) -> u64 {
     account_id.as_str().len() as u64 + 4 + size_of::<u64>() as u64
 }
@@ -31,11 +72,15 @@ where
     Promise::new(account_id).transfer(Balance::from(storage_released) * env::storage_byte_cost())
 }
 
-pub(crate) fn refund_approved_account_ids(
-    account_id: AccountId,
-    approved_account_ids: &HashMap<AccountId, u64>,
-) -> Promise {
-    refund_approved_account_ids_iter(account_id, approved_account_ids.keys())
+//refund the storage of all approvals on a token, including their expiries
+pub(crate) fn refund_token_approvals(account_id: AccountId, token: &Token) -> Promise {
+    refund_approved_account_ids_iter(
+        account_id,
+        token
+            .approved_account_ids
+            .keys()
+            .chain(token.approval_expires_at.keys()),
+    )
 }
 
 pub(crate) fn hash_account_id(account_id: &AccountId) -> CryptoHash {
@@ -44,6 +89,18 @@ pub(crate) fn hash_account_id(account_id: &AccountId) -> CryptoHash {
     hash
 }
 
+pub(crate) fn hash_token_id(token_id: &TokenId) -> CryptoHash {
+    let mut hash = CryptoHash::default();
+    hash.copy_from_slice(&env::sha256(token_id.as_bytes()));
+    hash
+}
+
+pub(crate) fn hash_category(category: &str) -> CryptoHash {
+    let mut hash = CryptoHash::default();
+    hash.copy_from_slice(&env::sha256(category.as_bytes()));
+    hash
+}
+
 pub(crate) fn refund_deposit(storage_used: u64) {
     let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
 
@@ -62,7 +119,104 @@ pub(crate) fn refund_deposit(storage_used: u64) {
     }
 }
 
+//refund the storage freed since `initial_storage_usage` to the account. Returns how many bytes were freed
+pub(crate) fn refund_freed_storage(account_id: AccountId, initial_storage_usage: u64) -> u64 {
+    let freed_storage_in_bytes = initial_storage_usage.saturating_sub(env::storage_usage());
+    let refund = Balance::from(freed_storage_in_bytes) * env::storage_byte_cost();
+    if refund > 0 {
+        Promise::new(account_id).transfer(refund);
+    }
+    freed_storage_in_bytes
+}
+
+//the account paying for the storage a transfer adds: the sender, or the seller for sales of the contract's
+//own listings, whose sale storage was just released
+pub(crate) fn transfer_payer_id<'a>(
+    sender_id: &'a AccountId,
+    owner_id: &'a AccountId,
+) -> &'a AccountId {
+    if sender_id == &env::current_account_id() {
+        owner_id
+    } else {
+        sender_id
+    }
+}
+
 impl Contract {
+    //take `cost` from the payer's storage deposit, leaving the part reserved for sales alone. Panics if the deposit can't cover it
+    pub(crate) fn internal_take_storage_deposit(&mut self, payer_id: &AccountId, cost: Balance) {
+        let balance = self.storage_deposits.get(payer_id).unwrap_or(0);
+        let reserved =
+            u128::from(self.get_supply_by_owner_id(payer_id.clone()).0) * STORAGE_PER_SALE;
+        assert!(
+            balance.saturating_sub(reserved) >= cost,
+            "Not enough storage deposit, {} yoctoNEAR required",
+            cost
+        );
+        if cost > 0 {
+            self.storage_deposits.insert(payer_id, &(balance - cost));
+        }
+    }
+
+    //give storage taken up front but never used back to the payer's storage deposit
+    pub(crate) fn internal_return_storage_deposit(
+        &mut self,
+        payer_id: &AccountId,
+        amount: Balance,
+    ) {
+        if amount > 0 {
+            let balance = self.storage_deposits.get(payer_id).unwrap_or(0);
+            self.storage_deposits.insert(payer_id, &(balance + amount));
+        }
+    }
+
+    //move a token from the old owner's index to the new owner's. What this adds, like the new owner's
+    //series stats entry, is taken from the payer's storage deposit. Without a payer the storage is already paid for
+    pub(crate) fn internal_change_token_owner(
+        &mut self,
+        token_id: &TokenId,
+        old_owner_id: &AccountId,
+        new_owner_id: &AccountId,
+        payer_id: Option<&AccountId>,
+    ) {
+        let initial_storage_usage = env::storage_usage();
+        self.internal_remove_token_from_owner(old_owner_id, token_id);
+        self.internal_add_token_to_owner(new_owner_id, token_id);
+        if let Some(payer_id) = payer_id {
+            let required_cost =
+                Balance::from(env::storage_usage().saturating_sub(initial_storage_usage))
+                    * env::storage_byte_cost();
+            self.internal_take_storage_deposit(payer_id, required_cost);
+        }
+    }
+
+    //panic if the token belongs to a soulbound series, or has expired in a series that enforces expiry
+    pub(crate) fn internal_assert_transferable(&self, token_id: &TokenId, token: &Token) {
+        if let Some(series) = self.series_by_id.get(&token.series_id) {
+            require!(!series.soulbound, "Token is soulbound");
+            if series.expiry_enforced {
+                require!(!self.internal_is_expired(token_id), "Token has expired");
+            }
+        }
+    }
+
+    //whether the token has passed its expires_at timestamp
+    pub(crate) fn internal_is_expired(&self, token_id: &TokenId) -> bool {
+        self.token_metadata_by_id
+            .get(token_id)
+            .is_some_and(|metadata| metadata.is_expired(env::block_timestamp_ms()))
+    }
+
+    //get the royalty that is actually paid out for a token, taking the series redirects into account
+    pub(crate) fn internal_token_royalty(&self, token: &Token) -> HashMap<AccountId, u32> {
+        match self.series_by_id.get(&token.series_id) {
+            Some(series) if !series.royalty_redirects.is_empty() => {
+                apply_royalty_redirects(&token.royalty, &series.royalty_redirects)
+            }
+            _ => token.royalty.clone(),
+        }
+    }
+
     pub(crate) fn internal_add_token_to_owner(
         &mut self,
         account_id: &AccountId,
@@ -79,6 +233,10 @@ impl Contract {
         });
         tokens_set.insert(token_id);
         self.token_per_owner.insert(account_id, &tokens_set);
+
+        if let Some(token) = self.token_by_id.get(token_id) {
+            self.internal_stats_add_holder(token.series_id, account_id);
+        }
     }
 
     pub(crate) fn internal_add_series_to_owner(
@@ -99,20 +257,37 @@ impl Contract {
         self.series_per_owner.insert(account_id, &series_set);
     }
 
-    pub(crate) fn internal_transfer(
+    //check that the sender can transfer the token and move it to the receiver without logging anything.
+    //Nested tokens move along with it. Returns the token as it was before the transfer and the IDs of the nested tokens that moved
+    pub(crate) fn internal_move_token(
         &mut self,
         sender_id: &AccountId,
         receiver_id: &AccountId,
         token_id: &TokenId,
         approval_id: Option<u64>,
-        memo: Option<String>,
-    ) -> Token {
+    ) -> (Token, Vec<TokenId>) {
         let token = self.token_by_id.get(token_id).expect("No token");
+        self.internal_assert_transferable(token_id, &token);
+        require!(
+            self.token_parent.get(token_id).is_none(),
+            "Token is nested and moves with its parent"
+        );
+
+        //operators approved for the owner's tokens don't need a per token approval
+        let is_operator = approval_id.is_none()
+            && self.nft_is_operator(
+                token.owner_id.clone(),
+                sender_id.clone(),
+                Some(token.series_id),
+            );
 
-        if sender_id != &token.owner_id {
+        if sender_id != &token.owner_id && !is_operator {
             if !token.approved_account_ids.contains_key(sender_id) {
                 env::panic_str("Unauthorized");
             }
+            if token.is_approval_expired(sender_id, env::block_timestamp_ms()) {
+                env::panic_str("Approval has expired");
+            }
 
             if let Some(enforced_approval_id) = approval_id {
                 let actual_approval_id = token
@@ -131,8 +306,11 @@ impl Contract {
             "The token is already owned by the receiver"
         );
 
-        self.internal_remove_token_from_owner(&token.owner_id, token_id);
-        self.internal_add_token_to_owner(receiver_id, token_id);
+        //the old owner's listing can no longer be bought once the token moves
+        self.internal_remove_native_sale(token_id);
+
+        let payer_id = transfer_payer_id(sender_id, &token.owner_id);
+        self.internal_change_token_owner(token_id, &token.owner_id, receiver_id, Some(payer_id));
 
         let new_token = Token {
             series_id: token.series_id,
@@ -140,18 +318,49 @@ impl Contract {
             approved_account_ids: Default::default(),
             next_approval_id: 0,
             royalty: token.royalty.clone(), //change this  when listing
+            approval_expires_at: Default::default(),
         };
 
         self.token_by_id.insert(token_id, &new_token);
 
+        let children =
+            self.internal_move_children(token_id, &token.owner_id, receiver_id, Some(payer_id));
+
+        (token, children)
+    }
+
+    pub(crate) fn internal_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_id: &TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        is_sale: bool,
+    ) -> (Token, Vec<TokenId>) {
+        let (token, children) =
+            self.internal_move_token(sender_id, receiver_id, token_id, approval_id);
+
+        //record the new owner of the token and of everything nested in it
+        let payer_id = transfer_payer_id(sender_id, &token.owner_id);
+        for moved_token_id in [vec![token_id.clone()], children.clone()].concat() {
+            self.internal_record_provenance(
+                &moved_token_id,
+                receiver_id,
+                &memo,
+                is_sale,
+                Some(payer_id),
+            );
+        }
+
         if let Some(memo) = memo.as_ref() {
             env::log_str(&format!("memo: {}", memo).to_string());
         }
 
         // Default the authorized ID to be None for the logs.
         let mut authorized_id = None;
-        //if the approval ID was provided, set the authorized ID equal to the sender
-        if approval_id.is_some() {
+        //if the approval ID was provided or an operator moved the token, set the authorized ID equal to the sender
+        if approval_id.is_some() || sender_id != &token.owner_id {
             authorized_id = Some(sender_id.to_string());
         }
 
@@ -169,8 +378,8 @@ impl Contract {
                 old_owner_id: token.owner_id.to_string(),
                 // The account ID of the new owner of the token.
                 new_owner_id: receiver_id.to_string(),
-                // A vector containing the token IDs as strings.
-                token_ids: vec![token_id.to_string()],
+                // A vector containing the token IDs as strings, nested tokens included.
+                token_ids: [vec![token_id.to_string()], children.clone()].concat(),
                 // An optional memo to include.
                 memo,
             }]),
@@ -179,7 +388,38 @@ impl Contract {
         // Log the serialized json.
         env::log_str(&nft_transfer_log.to_string());
 
-        token
+        (token, children)
+    }
+
+    //remove a token and everything attached to it. Returns the burned token and the storage usage before the token's
+    //own storage was freed, so the caller can refund it through `refund_freed_storage`
+    pub(crate) fn internal_burn(&mut self, token_id: &TokenId) -> (Token, u64) {
+        let token = self.token_by_id.get(token_id).expect("No token");
+        self.internal_assert_no_unfinished_snapshot(token.series_id);
+
+        //drop any active sale first. Its storage is covered by the seller's storage deposit, not the token
+        self.internal_remove_native_sale(token_id);
+
+        let initial_storage_usage = env::storage_usage();
+
+        //nested tokens are released to the owner and the token is detached from its parent
+        self.internal_detach_nesting(token_id);
+
+        //the token is still needed to update the series stats of its owner
+        self.internal_remove_token_from_owner(&token.owner_id, token_id);
+        self.token_by_id.remove(token_id);
+        self.token_metadata_by_id.remove(token_id);
+        self.token_metadata_history.remove(token_id);
+        if let Some(mut provenance) = self.provenance_by_token.remove(token_id) {
+            provenance.clear();
+        }
+
+        if let Some(mut series) = self.series_by_id.get(&token.series_id) {
+            series.tokens.remove(token_id);
+            self.series_by_id.insert(&token.series_id, &series);
+        }
+
+        (token, initial_storage_usage)
     }
 
     pub(crate) fn internal_remove_token_from_owner(
@@ -197,6 +437,69 @@ impl Contract {
         } else {
             self.token_per_owner.insert(account_id, &tokens_set);
         }
+
+        if let Some(token) = self.token_by_id.get(token_id) {
+            self.internal_stats_remove_holder(token.series_id, account_id);
+        }
+    }
+
+    pub(crate) fn internal_add_series_to_category(&mut self, category: &str, series_id: &SeriesId) {
+        let category = category.to_string();
+        let mut series_set = self.series_by_category.get(&category).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::SeriesByCategoryInner {
+                    category_hash: hash_category(&category),
+                }
+                .try_to_vec()
+                .unwrap(),
+            )
+        });
+        series_set.insert(series_id);
+        self.series_by_category.insert(&category, &series_set);
+    }
+
+    pub(crate) fn internal_remove_series_from_category(
+        &mut self,
+        category: &str,
+        series_id: &SeriesId,
+    ) {
+        let category = category.to_string();
+        if let Some(mut series_set) = self.series_by_category.get(&category) {
+            series_set.remove(series_id);
+            if series_set.is_empty() {
+                self.series_by_category.remove(&category);
+            } else {
+                self.series_by_category.insert(&category, &series_set);
+            }
+        }
+    }
+
+    pub(crate) fn internal_remove_series_from_owner(
+        &mut self,
+        account_id: &AccountId,
+        series_id: &SeriesId,
+    ) {
+        let mut series_set = self
+            .series_per_owner
+            .get(account_id)
+            .expect("Series should be owned by the account");
+        series_set.remove(series_id);
+        if series_set.is_empty() {
+            self.series_per_owner.remove(account_id);
+        } else {
+            self.series_per_owner.insert(account_id, &series_set);
+        }
+    }
+
+    //remove the sale of a token minted on this contract if there is one. The seller's storage deposit is freed up again
+    pub(crate) fn internal_remove_native_sale(&mut self, token_id: &TokenId) -> Option<Sale> {
+        let contract_and_token_id =
+            format!("{}{}{}", env::current_account_id(), DELIMETER, token_id);
+        if self.sales.get(&contract_and_token_id).is_some() {
+            Some(self.internal_remove_sale(env::current_account_id(), token_id.clone()))
+        } else {
+            None
+        }
     }
 
     pub(crate) fn internal_remove_sale(
@@ -208,6 +511,9 @@ impl Contract {
         let contract_and_token_id = format!("{}{}{}", &nft_contract_id, DELIMETER, token_id);
         //get the sale object by removing the unique sale ID. If there was no sale, panic
         let sale = self.sales.remove(&contract_and_token_id).expect("No sale");
+        if nft_contract_id == env::current_account_id() {
+            self.internal_stats_remove_listing(&token_id, sale.sale_conditions.0);
+        }
 
         //get the set of sales for the sale's owner. If there's no sale, panic.
         let mut by_owner_id = self
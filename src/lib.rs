@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use near_sdk::{
     assert_one_yocto,
     borsh::{self, BorshDeserialize, BorshSerialize},
-    collections::{LazyOption, LookupMap, UnorderedMap, UnorderedSet},
+    collections::{LazyOption, LookupMap, UnorderedMap, UnorderedSet, Vector},
     env::{self, STORAGE_PRICE_PER_BYTE},
     json_types::{Base64VecU8, U128},
     near_bindgen, require,
@@ -12,29 +12,48 @@ use near_sdk::{
 };
 
 pub use crate::approval::*;
+pub use crate::earnings::*;
 pub use crate::events::*;
 use crate::internal::*;
 pub use crate::metadata::*;
 pub use crate::mint::*;
 pub use crate::nft_core::*;
+pub use crate::operator::*;
+pub use crate::provenance::*;
 pub use crate::royalties::*;
 pub use crate::sale::*;
 pub use crate::sale_views::*;
 pub use crate::series::*;
+pub use crate::series_transfer::*;
+pub use crate::snapshot::*;
+pub use crate::stats::*;
 pub use crate::user::*;
 
 mod approval;
+mod burn;
+mod composable;
+mod curation;
+mod earnings;
 mod enumeration;
 mod events;
 mod internal;
 mod metadata;
+mod migrate;
 mod mint;
+mod minter;
 mod nft_core;
+mod operator;
+mod provenance;
 mod royalties;
 mod sale;
 mod sale_views;
 mod series;
+mod series_transfer;
+mod snapshot;
+mod stats;
+mod token_metadata;
 mod user;
+mod validity;
 
 pub const NFT_METADATA_SPEC: &str = "1.0.0";
 pub const NFT_STANDARD_NAME: &str = "nep171";
@@ -61,6 +80,24 @@ pub struct Contract {
     pub sale_by_owner: UnorderedMap<AccountId, UnorderedSet<TokenId>>,
     pub sales: UnorderedMap<TokenId, Sale>,
     pub by_nft_contract_id: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub earnings_by_account: LookupMap<AccountId, CreatorEarnings>,
+    pub earnings_by_series: LookupMap<SeriesId, CreatorEarnings>,
+    pub token_metadata_history: LookupMap<TokenId, Vec<TokenMetadata>>,
+    pub token_parent: LookupMap<TokenId, TokenId>,
+    pub token_children: LookupMap<TokenId, UnorderedSet<TokenId>>,
+    pub operators_by_owner: LookupMap<AccountId, UnorderedMap<AccountId, Option<SeriesId>>>,
+    pub provenance_by_token: LookupMap<TokenId, Vector<ProvenanceEntry>>,
+    pub snapshots_by_series: LookupMap<SeriesId, UnorderedMap<u64, Snapshot>>,
+    pub minters_by_series: LookupMap<SeriesId, UnorderedSet<AccountId>>,
+    pub pending_series_transfers: LookupMap<SeriesId, PendingSeriesTransfer>,
+    pub curated_creation: bool,
+    pub approved_creators: UnorderedSet<AccountId>,
+    pub curators: UnorderedSet<AccountId>,
+    pub verified_series: UnorderedSet<SeriesId>,
+    pub series_by_category: LookupMap<String, UnorderedSet<SeriesId>>,
+    pub stats_by_series: LookupMap<SeriesId, SeriesStats>,
+    pub holder_token_counts: LookupMap<(SeriesId, AccountId), u64>,
+    pub unfinished_snapshots: LookupMap<SeriesId, u64>,
 }
 
 #[derive(BorshStorageKey, BorshSerialize)]
@@ -82,6 +119,32 @@ pub enum StorageKey {
     ByNFTContractId,
     SaleByOwnerIdInner { account_id_hash: CryptoHash },
     ByNFTContractIdInner { account_id_hash: CryptoHash },
+    EarningsByAccount,
+    EarningsBySeries,
+    TokenMetadataHistory,
+    TokenParent,
+    TokenChildren,
+    TokenChildrenInner { token_id_hash: CryptoHash },
+    OperatorsByOwner,
+    OperatorsByOwnerInner { account_id_hash: CryptoHash },
+    ProvenanceByToken,
+    ProvenanceByTokenInner { token_id_hash: CryptoHash },
+    SnapshotsBySeries,
+    SnapshotsBySeriesInner { series_id: SeriesId },
+    SnapshotHoldersInner { series_id: u64, block_height: u64 },
+    SeriesTokensInner { series_id: SeriesId },
+    MintersBySeries,
+    MintersBySeriesInner { series_id: SeriesId },
+    PendingSeriesTransfers,
+    ApprovedCreators,
+    Curators,
+    VerifiedSeries,
+    SeriesByCategory,
+    SeriesByCategoryInner { category_hash: CryptoHash },
+    StatsBySeries,
+    SeriesListingsInner { series_id: SeriesId },
+    HolderTokenCounts,
+    UnfinishedSnapshots,
 }
 
 #[near_bindgen]
@@ -120,6 +183,24 @@ impl Contract {
             sale_by_owner: UnorderedMap::new(StorageKey::SaleByOwner),
             sales: UnorderedMap::new(StorageKey::Sales),
             by_nft_contract_id: LookupMap::new(StorageKey::ByNFTContractId),
+            earnings_by_account: LookupMap::new(StorageKey::EarningsByAccount),
+            earnings_by_series: LookupMap::new(StorageKey::EarningsBySeries),
+            token_metadata_history: LookupMap::new(StorageKey::TokenMetadataHistory),
+            token_parent: LookupMap::new(StorageKey::TokenParent),
+            token_children: LookupMap::new(StorageKey::TokenChildren),
+            operators_by_owner: LookupMap::new(StorageKey::OperatorsByOwner),
+            provenance_by_token: LookupMap::new(StorageKey::ProvenanceByToken),
+            snapshots_by_series: LookupMap::new(StorageKey::SnapshotsBySeries),
+            minters_by_series: LookupMap::new(StorageKey::MintersBySeries),
+            pending_series_transfers: LookupMap::new(StorageKey::PendingSeriesTransfers),
+            curated_creation: false,
+            approved_creators: UnorderedSet::new(StorageKey::ApprovedCreators),
+            curators: UnorderedSet::new(StorageKey::Curators),
+            verified_series: UnorderedSet::new(StorageKey::VerifiedSeries),
+            series_by_category: LookupMap::new(StorageKey::SeriesByCategory),
+            stats_by_series: LookupMap::new(StorageKey::StatsBySeries),
+            holder_token_counts: LookupMap::new(StorageKey::HolderTokenCounts),
+            unfinished_snapshots: LookupMap::new(StorageKey::UnfinishedSnapshots),
         }
     }
 
@@ -21,16 +21,92 @@ pub struct NFTContractMetadata {
     pub reference_hash: Option<Base64VecU8>,
 }
 
-#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
 #[serde(crate = "near_sdk::serde")]
 pub struct TokenMetadata {
     pub title: Option<String>,
     pub description: Option<String>,
     pub media: Option<String>,
-    pub media_hash: Option<String>,
+    pub media_hash: Option<Base64VecU8>,
     pub copies: Option<u64>,
     pub issued_at: Option<String>,
+    pub expires_at: Option<String>,
+    pub starts_at: Option<String>,
+    pub updated_at: Option<String>,
     pub extra: Option<String>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<Base64VecU8>,
+}
+
+impl TokenMetadata {
+    //panic if the metadata doesn't follow NEP-177
+    pub fn assert_valid(&self) {
+        require!(
+            self.media.is_some() == self.media_hash.is_some(),
+            "media and media_hash must be set together"
+        );
+        if let Some(media_hash) = &self.media_hash {
+            require!(media_hash.0.len() == 32, "media_hash has to be 32 bytes");
+        }
+
+        require!(
+            self.reference.is_some() == self.reference_hash.is_some(),
+            "reference and reference_hash must be set together"
+        );
+        if let Some(reference_hash) = &self.reference_hash {
+            require!(
+                reference_hash.0.len() == 32,
+                "reference_hash has to be 32 bytes"
+            );
+        }
+
+        if let Some(copies) = self.copies {
+            require!(copies > 0, "copies must be greater than 0");
+        }
+
+        //timestamps are unix epoch in milliseconds
+        let starts_at = parse_timestamp(&self.starts_at, "starts_at");
+        let expires_at = parse_timestamp(&self.expires_at, "expires_at");
+        parse_timestamp(&self.issued_at, "issued_at");
+        parse_timestamp(&self.updated_at, "updated_at");
+        if let (Some(starts_at), Some(expires_at)) = (starts_at, expires_at) {
+            require!(
+                starts_at < expires_at,
+                "starts_at must be before expires_at"
+            );
+        }
+    }
+}
+
+impl TokenMetadata {
+    //the expires_at timestamp in milliseconds, if the token expires
+    pub fn expires_at_ms(&self) -> Option<u64> {
+        self.expires_at
+            .as_ref()
+            .and_then(|expires_at| expires_at.parse::<u64>().ok())
+    }
+
+    //whether the token has passed its expires_at timestamp
+    pub fn is_expired(&self, now_ms: u64) -> bool {
+        self.expires_at_ms()
+            .is_some_and(|expires_at| now_ms >= expires_at)
+    }
+
+    //whether the token has reached its starts_at timestamp
+    pub fn has_started(&self, now_ms: u64) -> bool {
+        self.starts_at
+            .as_ref()
+            .and_then(|starts_at| starts_at.parse::<u64>().ok())
+            .is_none_or(|starts_at| now_ms >= starts_at)
+    }
+}
+
+fn parse_timestamp(timestamp: &Option<String>, field: &str) -> Option<u64> {
+    timestamp.as_ref().map(|timestamp| {
+        timestamp.parse::<u64>().unwrap_or_else(|_| {
+            env::panic_str(&format!("{} must be a timestamp in milliseconds", field))
+        })
+    })
 }
 
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -40,6 +116,17 @@ pub struct Token {
     pub approved_account_ids: HashMap<AccountId, u64>,
     pub next_approval_id: u64,
     pub royalty: HashMap<AccountId, u32>,
+    // When approvals given with nft_approve_until stop being valid (unix epoch in milliseconds)
+    pub approval_expires_at: HashMap<AccountId, u64>,
+}
+
+impl Token {
+    //whether the approval of an account has passed its expiry
+    pub fn is_approval_expired(&self, account_id: &AccountId, now_ms: u64) -> bool {
+        self.approval_expires_at
+            .get(account_id)
+            .is_some_and(|expires_at| now_ms >= *expires_at)
+    }
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
@@ -60,6 +147,15 @@ pub struct UserMetadata {
     pub banner_url: Option<String>,
 }
 
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoyaltyRedirect {
+    // Account that now receives the royalty
+    pub account_id: AccountId,
+    // Optional cap on the percentage paid out (royalties can only ever go down)
+    pub max_percentage: Option<u32>,
+}
+
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct Series {
     // Royalty used for all tokens in the collection
@@ -75,6 +171,47 @@ pub struct Series {
     pub owner_id: AccountId,
     //collection logo
     pub metadata: SeriesMetadata,
+    // Royalty recipients replaced by the series owner, applied lazily to every token
+    pub royalty_redirects: HashMap<AccountId, RoyaltyRedirect>,
+    // Whether accounts approved on a token can burn it on behalf of the owner
+    pub approved_can_burn: bool,
+    // Soulbound tokens can never be transferred, approved or listed. They can only be burned
+    pub soulbound: bool,
+    // Whether the series owner can update the metadata of minted tokens
+    pub mutable_metadata: bool,
+    // Expired tokens can't be transferred or listed, and anyone can sweep (burn) them
+    pub expiry_enforced: bool,
+    // Token IDs are derived as `{series_id}:{edition}` instead of being passed in when minting
+    pub edition_token_ids: bool,
+    // Number of editions that can be minted, also written into the metadata of every token
+    pub copies: Option<u64>,
+    // Whether a marketplace curator verified the series
+    pub verified: bool,
+    // A frozen series can never mint again
+    pub frozen: bool,
+    // Maximum number of tokens that can ever be minted, fixed when the series is created
+    pub max_supply: Option<u64>,
+    // Number of tokens minted so far, burned tokens included
+    pub minted_count: u64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(crate = "near_sdk::serde", default)]
+pub struct SeriesOptions {
+    // Whether accounts approved on a token can burn it on behalf of the owner
+    pub approved_can_burn: bool,
+    // Soulbound tokens can never be transferred, approved or listed. They can only be burned
+    pub soulbound: bool,
+    // Whether the series owner can update the metadata of minted tokens
+    pub mutable_metadata: bool,
+    // Expired tokens can't be transferred or listed, and anyone can sweep (burn) them
+    pub expiry_enforced: bool,
+    // Token IDs are derived as `{series_id}:{edition}` instead of being passed in when minting
+    pub edition_token_ids: bool,
+    // Number of editions that can be minted
+    pub copies: Option<u64>,
+    // Maximum number of tokens that can ever be minted
+    pub max_supply: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -102,6 +239,26 @@ pub struct JsonSeries {
     pub volume: Option<Balance>,
     // price
     pub price: Option<Balance>,
+    // Whether approved accounts can burn tokens
+    pub approved_can_burn: bool,
+    // Whether tokens are non-transferable
+    pub soulbound: bool,
+    // Whether token metadata can be updated
+    pub mutable_metadata: bool,
+    // Whether expired tokens are locked and can be swept
+    pub expiry_enforced: bool,
+    // Whether token IDs are derived from the edition
+    pub edition_token_ids: bool,
+    // Number of editions
+    pub copies: Option<u64>,
+    // Whether the series is verified by a curator
+    pub verified: bool,
+    // Whether minting is locked for good
+    pub frozen: bool,
+    // Maximum number of tokens that can ever be minted
+    pub max_supply: Option<u64>,
+    // Number of tokens minted so far
+    pub minted_count: u64,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -120,3 +277,77 @@ impl NonFungibleTokenMetadata for Contract {
         self.metadata.get().unwrap()
     }
 }
+
+#[near_bindgen]
+impl Contract {
+    /// Replace the contract metadata. Only the contract owner can do this.
+    #[payable]
+    pub fn update_contract_metadata(
+        &mut self,
+        metadata: NFTContractMetadata,
+        memo: Option<String>,
+    ) {
+        assert_at_least_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only the contract owner can update the metadata"
+        );
+        if let Some(reference_hash) = &metadata.reference_hash {
+            require!(
+                reference_hash.0.len() == 32,
+                "reference_hash has to be 32 bytes"
+            );
+        }
+
+        self.metadata.set(&metadata);
+
+        let contract_metadata_update_log: EventLog = EventLog {
+            // Standard name ("nep171").
+            standard: NFT_STANDARD_NAME.to_string(),
+            // Version of the standard ("nft-1.0.0").
+            version: NFT_METADATA_SPEC.to_string(),
+            // The data related with the event stored in a vector.
+            event: EventLogVariant::ContractMetadataUpdate(vec![ContractMetadataUpdateLog {
+                // An optional memo to include.
+                memo,
+            }]),
+        };
+
+        // Log the serialized json.
+        env::log_str(&contract_metadata_update_log.to_string());
+
+        //the new metadata can be smaller than the old one, only charge for growth
+        let required_storage_in_bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+        refund_deposit(required_storage_in_bytes);
+    }
+
+    //get the metadata of a token with relative media and reference links resolved against the contract base_uri
+    pub fn nft_resolved_metadata(&self, token_id: TokenId) -> Option<TokenMetadata> {
+        let mut metadata = self.token_metadata_by_id.get(&token_id)?;
+        let base_uri = self.metadata.get().and_then(|metadata| metadata.base_uri);
+
+        if let Some(base_uri) = base_uri {
+            metadata.media = metadata.media.map(|media| resolve_uri(&base_uri, media));
+            metadata.reference = metadata
+                .reference
+                .map(|reference| resolve_uri(&base_uri, reference));
+        }
+
+        Some(metadata)
+    }
+}
+
+//join a relative path onto the base uri. Links that already have a scheme are left untouched
+fn resolve_uri(base_uri: &str, uri: String) -> String {
+    if uri.contains("://") || uri.starts_with("data:") {
+        uri
+    } else {
+        format!(
+            "{}/{}",
+            base_uri.trim_end_matches('/'),
+            uri.trim_start_matches('/')
+        )
+    }
+}
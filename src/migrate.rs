@@ -0,0 +1,565 @@
+use std::io;
+
+use crate::*;
+
+/// A stored value kept as raw bytes. Used to read entries that may still be in an older layout.
+pub struct RawValue(Vec<u8>);
+
+impl BorshSerialize for RawValue {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.0)
+    }
+}
+
+impl BorshDeserialize for RawValue {
+    fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+        let value = RawValue(buf.to_vec());
+        *buf = &[];
+        Ok(value)
+    }
+}
+
+//the storage prefix of a token set. Series created before every series had its own prefix share the
+//prefix of their creator
+fn token_set_prefix(tokens: &UnorderedSet<TokenId>) -> Vec<u8> {
+    let bytes = tokens.try_to_vec().unwrap();
+    <Vec<u8> as BorshDeserialize>::deserialize(&mut bytes.as_slice()).unwrap()
+}
+
+//the same map with its values read as raw bytes. It shares the storage prefix and length of the live map,
+//so entries that don't deserialize into the current layout can still be read and rewritten
+fn raw_values<K, V>(map: &UnorderedMap<K, V>) -> UnorderedMap<K, RawValue>
+where
+    K: BorshSerialize + BorshDeserialize,
+    V: BorshSerialize,
+{
+    UnorderedMap::try_from_slice(&map.try_to_vec().unwrap()).unwrap()
+}
+
+//the contract state as deployed before the migration
+#[derive(BorshDeserialize)]
+pub struct OldContract {
+    pub owner_id: AccountId,
+    pub token_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub token_by_id: LookupMap<TokenId, Token>,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub user_metadata_by_owner: UnorderedMap<AccountId, UserMetadata>,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub series_by_id: UnorderedMap<SeriesId, Series>,
+    pub series_per_owner: LookupMap<AccountId, UnorderedSet<SeriesId>>,
+    pub storage_deposits: LookupMap<AccountId, Balance>,
+    pub sale_by_owner: UnorderedMap<AccountId, UnorderedSet<TokenId>>,
+    pub sales: UnorderedMap<TokenId, Sale>,
+    pub by_nft_contract_id: LookupMap<AccountId, UnorderedSet<TokenId>>,
+}
+
+//token metadata as stored before the migration
+#[derive(BorshDeserialize)]
+#[cfg_attr(test, derive(BorshSerialize))]
+pub struct OldTokenMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub media: Option<String>,
+    pub media_hash: Option<String>,
+    pub copies: Option<u64>,
+    pub issued_at: Option<String>,
+    pub extra: Option<String>,
+}
+
+//tokens as stored before the migration
+#[derive(BorshDeserialize)]
+pub struct OldToken {
+    pub series_id: u64,
+    pub owner_id: AccountId,
+    pub approved_account_ids: HashMap<AccountId, u64>,
+    pub next_approval_id: u64,
+    pub royalty: HashMap<AccountId, u32>,
+}
+
+//series as stored before the migration
+#[derive(BorshDeserialize)]
+#[cfg_attr(test, derive(BorshSerialize))]
+pub struct OldSeries {
+    pub royalty: Option<HashMap<AccountId, u32>>,
+    pub tokens: UnorderedSet<TokenId>,
+    pub price: Option<Balance>,
+    pub volume: Option<Balance>,
+    pub owner_id: AccountId,
+    pub metadata: SeriesMetadata,
+}
+
+impl From<OldTokenMetadata> for TokenMetadata {
+    fn from(old: OldTokenMetadata) -> Self {
+        Self {
+            title: old.title,
+            description: old.description,
+            media: old.media,
+            //hashes used to be stored as base64 strings. Anything that isn't base64 is kept as raw bytes
+            media_hash: old.media_hash.map(|media_hash| {
+                near_sdk::serde_json::from_value::<Base64VecU8>(media_hash.clone().into())
+                    .unwrap_or_else(|_| Base64VecU8(media_hash.into_bytes()))
+            }),
+            copies: old.copies,
+            issued_at: old.issued_at,
+            expires_at: None,
+            starts_at: None,
+            updated_at: None,
+            extra: old.extra,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+}
+
+impl From<OldToken> for Token {
+    fn from(old: OldToken) -> Self {
+        Self {
+            series_id: old.series_id,
+            owner_id: old.owner_id,
+            approved_account_ids: old.approved_account_ids,
+            next_approval_id: old.next_approval_id,
+            royalty: old.royalty,
+            approval_expires_at: HashMap::new(),
+        }
+    }
+}
+
+impl From<OldSeries> for Series {
+    fn from(old: OldSeries) -> Self {
+        Self {
+            royalty: old.royalty,
+            tokens: old.tokens,
+            price: old.price,
+            volume: old.volume,
+            owner_id: old.owner_id,
+            metadata: old.metadata,
+            royalty_redirects: HashMap::new(),
+            approved_can_burn: false,
+            soulbound: false,
+            mutable_metadata: false,
+            expiry_enforced: false,
+            edition_token_ids: false,
+            copies: None,
+            verified: false,
+            frozen: false,
+            max_supply: None,
+            //token sets can still be shared between series here, the count is rebuilt by migrate_series_token_sets
+            minted_count: 0,
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Upgrade the contract state after deploying new code. Series, tokens and token metadata are migrated
+    /// afterwards in pages through `migrate_series`, `migrate_tokens` and `migrate_token_metadata`, then the
+    /// series token sets through `migrate_series_prefixes` and `migrate_series_token_sets` and the category
+    /// index through `migrate_series_categories`. The series stats are filled while migrating the tokens. Once the
+    /// token sets are rebuilt, the sets series used to share are cleared through `migrate_clear_shared_token_set`.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let old: OldContract = env::state_read().expect("failed");
+
+        Self {
+            owner_id: old.owner_id,
+            token_per_owner: old.token_per_owner,
+            token_by_id: old.token_by_id,
+            token_metadata_by_id: old.token_metadata_by_id,
+            user_metadata_by_owner: old.user_metadata_by_owner,
+            metadata: old.metadata,
+            series_by_id: old.series_by_id,
+            series_per_owner: old.series_per_owner,
+            storage_deposits: old.storage_deposits,
+            sale_by_owner: old.sale_by_owner,
+            sales: old.sales,
+            by_nft_contract_id: old.by_nft_contract_id,
+            earnings_by_account: LookupMap::new(StorageKey::EarningsByAccount),
+            earnings_by_series: LookupMap::new(StorageKey::EarningsBySeries),
+            token_metadata_history: LookupMap::new(StorageKey::TokenMetadataHistory),
+            token_parent: LookupMap::new(StorageKey::TokenParent),
+            token_children: LookupMap::new(StorageKey::TokenChildren),
+            operators_by_owner: LookupMap::new(StorageKey::OperatorsByOwner),
+            provenance_by_token: LookupMap::new(StorageKey::ProvenanceByToken),
+            snapshots_by_series: LookupMap::new(StorageKey::SnapshotsBySeries),
+            minters_by_series: LookupMap::new(StorageKey::MintersBySeries),
+            pending_series_transfers: LookupMap::new(StorageKey::PendingSeriesTransfers),
+            curated_creation: false,
+            approved_creators: UnorderedSet::new(StorageKey::ApprovedCreators),
+            curators: UnorderedSet::new(StorageKey::Curators),
+            verified_series: UnorderedSet::new(StorageKey::VerifiedSeries),
+            series_by_category: LookupMap::new(StorageKey::SeriesByCategory),
+            stats_by_series: LookupMap::new(StorageKey::StatsBySeries),
+            holder_token_counts: LookupMap::new(StorageKey::HolderTokenCounts),
+            unfinished_snapshots: LookupMap::new(StorageKey::UnfinishedSnapshots),
+        }
+    }
+
+    /// Rewrite a page of series into the current layout. Series that are already migrated are skipped,
+    /// so pages can safely be run again. Returns how many series were migrated.
+    pub fn migrate_series(&mut self, from_index: Option<U128>, limit: Option<u64>) -> u64 {
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only the contract owner can migrate"
+        );
+
+        let mut raw_series = raw_values(&self.series_by_id);
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+        let series_ids: Vec<SeriesId> = self
+            .series_by_id
+            .keys()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect();
+
+        let mut migrated = 0;
+        for series_id in series_ids {
+            let raw = raw_series.get(&series_id).unwrap();
+            if let Ok(old) = OldSeries::try_from_slice(&raw.0) {
+                let series: Series = old.into();
+                raw_series.insert(&series_id, &RawValue(series.try_to_vec().unwrap()));
+                migrated += 1;
+            }
+        }
+        migrated
+    }
+
+    /// Move a page of series that still share their creator's token set prefix to a set of their own.
+    /// The new sets start empty and are filled by `migrate_series_token_sets`, which must run after every
+    /// series has been moved. Series that already have their own prefix are skipped. Returns how many series were moved.
+    pub fn migrate_series_prefixes(&mut self, from_index: Option<U128>, limit: Option<u64>) -> u64 {
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only the contract owner can migrate"
+        );
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+        let page: Vec<(SeriesId, Series)> = self
+            .series_by_id
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect();
+
+        let mut migrated = 0;
+        for (series_id, mut series) in page {
+            let tokens = UnorderedSet::new(StorageKey::SeriesTokensInner { series_id });
+            if token_set_prefix(&series.tokens) == token_set_prefix(&tokens) {
+                continue;
+            }
+            series.tokens = tokens;
+            self.series_by_id.insert(&series_id, &series);
+            migrated += 1;
+        }
+        migrated
+    }
+
+    /// Rebuild the token sets of the series from `token_by_id`, paginating over all token IDs, and count the
+    /// minted tokens of every series from its set. Burned tokens weren't counted before, so the current supply
+    /// is the best estimate. Adding a token that is already in its series' set does nothing, so pages can
+    /// safely be run again. Returns how many tokens were added.
+    pub fn migrate_series_token_sets(
+        &mut self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> u64 {
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only the contract owner can migrate"
+        );
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+        let token_ids: Vec<TokenId> = self
+            .token_metadata_by_id
+            .keys()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect();
+
+        let mut migrated = 0;
+        for token_id in token_ids {
+            let token = if let Some(token) = self.token_by_id.get(&token_id) {
+                token
+            } else {
+                continue;
+            };
+            let mut series = if let Some(series) = self.series_by_id.get(&token.series_id) {
+                series
+            } else {
+                continue;
+            };
+            //series that already had their own set keep it, but still need their count
+            let added = series.tokens.insert(&token_id);
+            let minted_count = series.minted_count.max(series.tokens.len());
+            if added || minted_count != series.minted_count {
+                series.minted_count = minted_count;
+                self.series_by_id.insert(&token.series_id, &series);
+            }
+            if added {
+                migrated += 1;
+            }
+        }
+        migrated
+    }
+
+    /// Clear a page of the token set the series of an account shared before every series had its own,
+    /// starting at `from_index` of the old set. Every series with tokens in the page has to be moved by
+    /// `migrate_series_prefixes` first, and the tokens have to be migrated. Returns how many tokens were cleared,
+    /// fewer than `limit` once the end of the set is reached.
+    pub fn migrate_clear_shared_token_set(
+        &mut self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> u64 {
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only the contract owner can migrate"
+        );
+
+        let shared_prefix = StorageKey::SeriesByIdInner {
+            account_id_hash: hash_account_id(&account_id),
+        }
+        .try_to_vec()
+        .unwrap();
+        let start = u128::from(from_index.unwrap_or(U128(0))) as u64;
+
+        //the length of the shared set was only kept in the series, so the page ends at the first missing element
+        let mut cleared = 0;
+        for index in start..start + limit.unwrap_or(50) {
+            let element_key = [shared_prefix.as_slice(), b"e", &index.to_le_bytes()].concat();
+            let raw_token_id = if let Some(raw_token_id) = env::storage_read(&element_key) {
+                raw_token_id
+            } else {
+                break;
+            };
+
+            let token_id = TokenId::try_from_slice(&raw_token_id).unwrap();
+            if let Some(token) = self.token_by_id.get(&token_id) {
+                require!(
+                    self.series_by_id
+                        .get(&token.series_id)
+                        .is_none_or(|series| token_set_prefix(&series.tokens) != shared_prefix),
+                    "Every series using the token set has to be moved to its own set first"
+                );
+            }
+
+            env::storage_remove(&[shared_prefix.as_slice(), b"i", &raw_token_id].concat());
+            env::storage_remove(&element_key);
+            cleared += 1;
+        }
+        cleared
+    }
+
+    /// Add a page of series to the category index. Adding a series that is already indexed does nothing,
+    /// so pages can safely be run again. Returns how many series were added.
+    pub fn migrate_series_categories(
+        &mut self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> u64 {
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only the contract owner can migrate"
+        );
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+        let page: Vec<(SeriesId, Series)> = self
+            .series_by_id
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect();
+
+        let mut migrated = 0;
+        for (series_id, series) in page {
+            if let Some(category) = &series.metadata.category {
+                let indexed = self
+                    .series_by_category
+                    .get(category)
+                    .is_some_and(|series_set| series_set.contains(&series_id));
+                if !indexed {
+                    self.internal_add_series_to_category(category, &series_id);
+                    migrated += 1;
+                }
+            }
+        }
+        migrated
+    }
+
+    /// Rewrite a page of tokens into the current layout, paginating over all token IDs, and count them in the
+    /// series stats along with their listings. Tokens that are already migrated are skipped, so pages can safely
+    /// be run again. Returns how many tokens were migrated.
+    pub fn migrate_tokens(&mut self, from_index: Option<U128>, limit: Option<u64>) -> u64 {
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only the contract owner can migrate"
+        );
+
+        let mut raw_tokens: LookupMap<TokenId, RawValue> = LookupMap::new(StorageKey::TokenById);
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+        let token_ids: Vec<TokenId> = self
+            .token_metadata_by_id
+            .keys()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect();
+
+        let mut migrated = 0;
+        for token_id in token_ids {
+            let raw = if let Some(raw) = raw_tokens.get(&token_id) {
+                raw
+            } else {
+                continue;
+            };
+            if let Ok(old) = OldToken::try_from_slice(&raw.0) {
+                let token: Token = old.into();
+                raw_tokens.insert(&token_id, &RawValue(token.try_to_vec().unwrap()));
+
+                //tokens in the old layout can't move, so they are counted exactly once, here
+                self.internal_stats_add_holder(token.series_id, &token.owner_id);
+                let contract_and_token_id =
+                    format!("{}{}{}", env::current_account_id(), DELIMETER, token_id);
+                if let Some(sale) = self.sales.get(&contract_and_token_id) {
+                    self.internal_stats_add_listing(&token_id, sale.sale_conditions.0);
+                }
+                migrated += 1;
+            }
+        }
+        migrated
+    }
+
+    /// Rewrite a page of token metadata into the NEP-177 layout. Tokens that are already migrated are skipped,
+    /// so pages can safely be run again. Returns how many tokens were migrated.
+    pub fn migrate_token_metadata(&mut self, from_index: Option<U128>, limit: Option<u64>) -> u64 {
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only the contract owner can migrate"
+        );
+
+        let mut raw_metadata = raw_values(&self.token_metadata_by_id);
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+        let token_ids: Vec<TokenId> = self
+            .token_metadata_by_id
+            .keys()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect();
+
+        let mut migrated = 0;
+        for token_id in token_ids {
+            let raw = raw_metadata.get(&token_id).unwrap();
+            if let Ok(old) = OldTokenMetadata::try_from_slice(&raw.0) {
+                let metadata: TokenMetadata = old.into();
+                raw_metadata.insert(&token_id, &RawValue(metadata.try_to_vec().unwrap()));
+                migrated += 1;
+            }
+        }
+        migrated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+
+    //a contract whose series and token metadata were written by the previous version
+    fn contract_with_old_state() -> Contract {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .build());
+        let mut contract = Contract::new_default(accounts(0));
+
+        let mut raw_series = raw_values(&contract.series_by_id);
+        let old_series = OldSeries {
+            royalty: None,
+            tokens: UnorderedSet::new(StorageKey::SeriesByIdInner {
+                account_id_hash: hash_account_id(&accounts(0)),
+            }),
+            price: None,
+            volume: None,
+            owner_id: accounts(0),
+            metadata: SeriesMetadata {
+                name: Some("Series".to_string()),
+                description: None,
+                category: None,
+                logo_media: None,
+                banner_media: None,
+            },
+        };
+        raw_series.insert(&1, &RawValue(old_series.try_to_vec().unwrap()));
+        contract.series_by_id =
+            UnorderedMap::try_from_slice(&raw_series.try_to_vec().unwrap()).unwrap();
+
+        let mut raw_metadata = raw_values(&contract.token_metadata_by_id);
+        let old_metadata = OldTokenMetadata {
+            title: Some("Token".to_string()),
+            description: None,
+            media: None,
+            media_hash: None,
+            copies: Some(1),
+            issued_at: Some("1".to_string()),
+            extra: None,
+        };
+        raw_metadata.insert(
+            &"1:1".to_string(),
+            &RawValue(old_metadata.try_to_vec().unwrap()),
+        );
+        contract.token_metadata_by_id =
+            UnorderedMap::try_from_slice(&raw_metadata.try_to_vec().unwrap()).unwrap();
+
+        contract
+    }
+
+    #[test]
+    fn migrate_series_rewrites_old_layout() {
+        let mut contract = contract_with_old_state();
+
+        assert_eq!(contract.migrate_series(None, None), 1);
+        let series = contract.series_by_id.get(&1).unwrap();
+        assert_eq!(series.owner_id, accounts(0));
+        assert_eq!(series.metadata.name, Some("Series".to_string()));
+
+        //migrated series are skipped when the page runs again
+        assert_eq!(contract.migrate_series(None, None), 0);
+    }
+
+    #[test]
+    fn migrate_token_metadata_rewrites_old_layout() {
+        let mut contract = contract_with_old_state();
+        let token_id = "1:1".to_string();
+
+        assert_eq!(contract.migrate_token_metadata(None, None), 1);
+        let metadata = contract.token_metadata_by_id.get(&token_id).unwrap();
+        assert_eq!(metadata.title, Some("Token".to_string()));
+        assert_eq!(metadata.copies, Some(1));
+        assert!(metadata.expires_at.is_none());
+
+        assert_eq!(contract.migrate_token_metadata(None, None), 0);
+    }
+
+    #[test]
+    fn migrate_clear_shared_token_set_removes_every_element() {
+        let mut contract = contract_with_old_state();
+        let mut shared_tokens: UnorderedSet<TokenId> =
+            UnorderedSet::new(StorageKey::SeriesByIdInner {
+                account_id_hash: hash_account_id(&accounts(0)),
+            });
+        shared_tokens.insert(&"1:1".to_string());
+        shared_tokens.insert(&"1:2".to_string());
+
+        assert_eq!(
+            contract.migrate_clear_shared_token_set(accounts(0), None, None),
+            2
+        );
+        assert!(!shared_tokens.contains(&"1:1".to_string()));
+
+        assert_eq!(
+            contract.migrate_clear_shared_token_set(accounts(0), None, None),
+            0
+        );
+    }
+}
@@ -2,13 +2,19 @@ use near_sdk::json_types::U64;
 
 use crate::*;
 
+//the maximum number of tokens that can be minted in a single batch (this is limited by GAS).
+//Every mint also writes the owner index, series stats and provenance, so stay well below what 300 TGas allows
+const MAX_BATCH_MINT: usize = 50;
+//gas kept free before minting another token in a batch, so the event and refund always fit
+const GAS_FOR_MINT: Gas = Gas(20_000_000_000_000);
+
 #[near_bindgen]
 impl Contract {
     #[payable]
     pub fn nft_mint(
         &mut self,
         id: U64,
-        token_id: TokenId,
+        token_id: Option<TokenId>,
         metadata: TokenMetadata,
         receiver_id: AccountId,
         perpetual_royalties: Option<HashMap<AccountId, u32>>,
@@ -17,53 +23,248 @@ impl Contract {
 
         // Get the series and how many tokens currently exist (edition number = cur_len + 1)
         let mut series = self.series_by_id.get(&id.0).expect("Not a series");
+        self.internal_assert_can_mint(id.0);
+
+        let royalty = internal_mint_royalty(perpetual_royalties);
+
+        let token_id = self.internal_mint(
+            &mut series,
+            id.0,
+            token_id,
+            metadata,
+            &receiver_id,
+            &royalty,
+        );
+        self.series_by_id.insert(&id.0, &series);
+
+        let nft_mint_log: EventLog = EventLog {
+            // Standard name ("nep171").
+            standard: NFT_STANDARD_NAME.to_string(),
+            // Version of the standard ("nft-1.0.0").
+            version: NFT_METADATA_SPEC.to_string(),
+            // The data related with the event stored in a vector.
+            event: EventLogVariant::NftMint(vec![NftMintLog {
+                // Owner of the token.
+                owner_id: receiver_id.to_string(),
+                // Vector of token IDs that were minted.
+                token_ids: vec![token_id],
+                // An optional memo to include.
+                memo: None,
+            }]),
+        };
+
+        // Log the serialized json.
+        env::log_str(&nft_mint_log.to_string());
+
+        let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
+
+        refund_deposit(required_storage_in_bytes);
+    }
+
+    /// Mint several tokens of a series at once. Storage for all tokens is paid in one go
+    /// and a single mint event is logged with the token IDs grouped per owner.
+    /// Token IDs are left out for series that derive edition token IDs.
+    #[payable]
+    pub fn nft_batch_mint(
+        &mut self,
+        id: U64,
+        tokens: Vec<(Option<TokenId>, TokenMetadata, AccountId)>,
+        perpetual_royalties: Option<HashMap<AccountId, u32>>,
+    ) {
+        let initial_storage_usage = env::storage_usage();
+
+        assert!(!tokens.is_empty(), "No tokens to mint");
+        assert!(
+            tokens.len() <= MAX_BATCH_MINT,
+            "Cannot mint more than {} tokens at once",
+            MAX_BATCH_MINT
+        );
+
+        let mut series = self.series_by_id.get(&id.0).expect("Not a series");
+        self.internal_assert_can_mint(id.0);
 
-        //Change this when listing
-        let mut royalty = HashMap::new();
+        let royalty = internal_mint_royalty(perpetual_royalties);
 
-        if let Some(perpetual_royalties) = perpetual_royalties {
+        //group the minted token IDs per owner for the event
+        let mut mint_logs: Vec<NftMintLog> = Vec::new();
+
+        let total = tokens.len();
+        for (minted, (token_id, metadata, receiver_id)) in tokens.into_iter().enumerate() {
+            //fail early with a clear message instead of running out of gas halfway through
             assert!(
-                perpetual_royalties.len() < 7,
-                "Cannot add more than 6 perpetual royalty amounts"
+                env::prepaid_gas() - env::used_gas() >= GAS_FOR_MINT,
+                "Not enough gas to mint every token, ran out after {} of {}",
+                minted,
+                total
+            );
+
+            let token_id = self.internal_mint(
+                &mut series,
+                id.0,
+                token_id,
+                metadata,
+                &receiver_id,
+                &royalty,
             );
-            for (account, amount) in perpetual_royalties {
-                royalty.insert(account, amount);
+
+            let owner_id = receiver_id.to_string();
+            if let Some(log) = mint_logs.iter_mut().find(|log| log.owner_id == owner_id) {
+                log.token_ids.push(token_id);
+            } else {
+                mint_logs.push(NftMintLog {
+                    owner_id,
+                    token_ids: vec![token_id],
+                    memo: None,
+                });
             }
         }
-        // finish up
+        self.series_by_id.insert(&id.0, &series);
 
-        let token = Token {
-            series_id: id.0,
-            owner_id: receiver_id,
-            approved_account_ids: Default::default(),
-            next_approval_id: 0,
-            royalty: royalty, //also this
+        let nft_mint_log: EventLog = EventLog {
+            // Standard name ("nep171").
+            standard: NFT_STANDARD_NAME.to_string(),
+            // Version of the standard ("nft-1.0.0").
+            version: NFT_METADATA_SPEC.to_string(),
+            // One entry per owner, each with the token IDs they received.
+            event: EventLogVariant::NftMint(mint_logs),
         };
 
+        // Log the serialized json.
+        env::log_str(&nft_mint_log.to_string());
+
+        let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
+
+        refund_deposit(required_storage_in_bytes);
+    }
+
+    /// Mint one edition of a series to each receiver, starting at `from_index`. Only the series owner
+    /// and approved minters can airdrop and the series must derive edition token IDs. Each call mints as many tokens as gas
+    /// allows and returns the index to pass in the next call, or None once every receiver got a token.
+    #[payable]
+    pub fn nft_airdrop(
+        &mut self,
+        id: U64,
+        receivers: Vec<AccountId>,
+        metadata: TokenMetadata,
+        perpetual_royalties: Option<HashMap<AccountId, u32>>,
+        from_index: Option<U128>,
+    ) -> Option<U128> {
+        let start = u128::from(from_index.unwrap_or(U128(0))) as usize;
+        assert!(start < receivers.len(), "No receivers left to airdrop to");
+        let total = receivers.len();
+
+        let next_index = start
+            + self.internal_airdrop(
+                id.0,
+                receivers.into_iter().skip(start),
+                metadata,
+                perpetual_royalties,
+            );
+        if next_index < total {
+            Some(U128(next_index as u128))
+        } else {
+            None
+        }
+    }
+
+    /// Airdrop one edition of a series to every holder recorded in a snapshot, see `nft_airdrop`.
+    /// The snapshot can belong to any series and has to be finalized.
+    #[payable]
+    pub fn nft_airdrop_to_snapshot(
+        &mut self,
+        id: U64,
+        snapshot_series_id: SeriesId,
+        block_height: U64,
+        metadata: TokenMetadata,
+        perpetual_royalties: Option<HashMap<AccountId, u32>>,
+        from_index: Option<U128>,
+    ) -> Option<U128> {
+        let snapshot = self
+            .snapshots_by_series
+            .get(&snapshot_series_id)
+            .and_then(|snapshots| snapshots.get(&block_height.0))
+            .expect("No snapshot");
+        require!(snapshot.finalized, "Snapshot is not finalized");
+
+        let start = u128::from(from_index.unwrap_or(U128(0))) as usize;
+        let total = snapshot.holders.len() as usize;
+        assert!(start < total, "No receivers left to airdrop to");
+
+        let next_index = start
+            + self.internal_airdrop(
+                id.0,
+                snapshot.holders.keys().skip(start),
+                metadata,
+                perpetual_royalties,
+            );
+        if next_index < total {
+            Some(U128(next_index as u128))
+        } else {
+            None
+        }
+    }
+}
+
+impl Contract {
+    //mint an edition to each receiver until the batch limit or the gas runs out. Storage is paid once
+    //for the whole call and a single mint event is logged. Returns how many receivers were minted to
+    fn internal_airdrop(
+        &mut self,
+        series_id: SeriesId,
+        receivers: impl Iterator<Item = AccountId>,
+        metadata: TokenMetadata,
+        perpetual_royalties: Option<HashMap<AccountId, u32>>,
+    ) -> usize {
+        let initial_storage_usage = env::storage_usage();
+
+        let mut series = self.series_by_id.get(&series_id).expect("Not a series");
+        self.internal_assert_can_mint(series_id);
         assert!(
-            self.token_by_id.insert(&token_id, &token).is_none(),
-            "Token already exists"
+            series.edition_token_ids,
+            "Airdrops need a series that derives edition token IDs"
         );
-        series.tokens.insert(&token_id);
 
-        self.token_metadata_by_id.insert(&token_id, &metadata);
+        let royalty = internal_mint_royalty(perpetual_royalties);
+
+        let mut mint_logs: Vec<NftMintLog> = Vec::new();
+        let mut minted = 0;
+
+        for receiver_id in receivers.take(MAX_BATCH_MINT) {
+            if env::prepaid_gas() - env::used_gas() < GAS_FOR_MINT {
+                break;
+            }
+
+            let token_id = self.internal_mint(
+                &mut series,
+                series_id,
+                None,
+                metadata.clone(),
+                &receiver_id,
+                &royalty,
+            );
+            minted += 1;
 
-        self.internal_add_token_to_owner(&token.owner_id, &token_id);
+            let owner_id = receiver_id.to_string();
+            if let Some(log) = mint_logs.iter_mut().find(|log| log.owner_id == owner_id) {
+                log.token_ids.push(token_id);
+            } else {
+                mint_logs.push(NftMintLog {
+                    owner_id,
+                    token_ids: vec![token_id],
+                    memo: None,
+                });
+            }
+        }
+        assert!(minted > 0, "Not enough gas to mint a single token");
+        self.series_by_id.insert(&series_id, &series);
 
         let nft_mint_log: EventLog = EventLog {
             // Standard name ("nep171").
             standard: NFT_STANDARD_NAME.to_string(),
             // Version of the standard ("nft-1.0.0").
             version: NFT_METADATA_SPEC.to_string(),
-            // The data related with the event stored in a vector.
-            event: EventLogVariant::NftMint(vec![NftMintLog {
-                // Owner of the token.
-                owner_id: token.owner_id.to_string(),
-                // Vector of token IDs that were minted.
-                token_ids: vec![token_id.to_string()],
-                // An optional memo to include.
-                memo: None,
-            }]),
+            // One entry per receiver, each with the token IDs they received.
+            event: EventLogVariant::NftMint(mint_logs),
         };
 
         // Log the serialized json.
@@ -72,5 +273,85 @@ impl Contract {
         let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
 
         refund_deposit(required_storage_in_bytes);
+
+        minted
+    }
+
+    //store a new token for the series and return its ID. The caller is responsible for writing the series back and logging the mint
+    pub(crate) fn internal_mint(
+        &mut self,
+        series: &mut Series,
+        series_id: SeriesId,
+        token_id: Option<TokenId>,
+        metadata: TokenMetadata,
+        receiver_id: &AccountId,
+        royalty: &HashMap<AccountId, u32>,
+    ) -> TokenId {
+        let mut metadata = metadata;
+
+        //frozen series and series at their max supply can't grow anymore, burned tokens included
+        assert!(!series.frozen, "Series is frozen");
+        self.internal_assert_no_unfinished_snapshot(series_id);
+        if let Some(max_supply) = series.max_supply {
+            assert!(series.minted_count < max_supply, "Max supply reached");
+        }
+
+        //the edition count can't go past the number of copies. Burned editions stay counted
+        if let Some(copies) = series.copies {
+            assert!(
+                series.minted_count < copies,
+                "All editions have been minted"
+            );
+            metadata.copies = Some(copies);
+        }
+        series.minted_count += 1;
+
+        let token_id = if series.edition_token_ids {
+            assert!(
+                token_id.is_none(),
+                "Token IDs are derived from the edition for this series"
+            );
+            //editions are never handed out twice, even after a burn
+            format!("{}:{}", series_id, series.minted_count)
+        } else {
+            let token_id = token_id.expect("Token ID is required");
+            //IDs of the edition form are left to edition series so their next edition is always free
+            assert!(
+                !token_id
+                    .split_once(':')
+                    .is_some_and(|(series_id, edition)| {
+                        series_id.parse::<u64>().is_ok() && edition.parse::<u64>().is_ok()
+                    }),
+                "Token IDs of the form {{series_id}}:{{edition}} are reserved"
+            );
+            token_id
+        };
+        let token_id = &token_id;
+
+        metadata.assert_valid();
+
+        let token = Token {
+            series_id,
+            owner_id: receiver_id.clone(),
+            approved_account_ids: Default::default(),
+            next_approval_id: 0,
+            royalty: royalty.clone(), //also this
+            approval_expires_at: Default::default(),
+        };
+
+        assert!(
+            self.token_by_id.insert(token_id, &token).is_none(),
+            "Token already exists"
+        );
+        series.tokens.insert(token_id);
+
+        self.token_metadata_by_id.insert(token_id, &metadata);
+
+        self.internal_add_token_to_owner(&token.owner_id, token_id);
+
+        //the first owner starts the provenance, its storage is part of the mint deposit
+        self.internal_record_provenance(token_id, receiver_id, &None, false, None);
+
+        token_id.clone()
     }
 }
@@ -0,0 +1,108 @@
+use near_sdk::assert_one_yocto;
+
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Allow an account to mint tokens of a series. Only the series owner can approve minters.
+    #[payable]
+    pub fn add_series_minter(&mut self, id: u64, account_id: AccountId) {
+        assert_at_least_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let series = self.series_by_id.get(&id).expect("Not a series");
+        assert_eq!(
+            env::predecessor_account_id(),
+            series.owner_id,
+            "Only the series owner can approve minters"
+        );
+        require!(
+            account_id != series.owner_id,
+            "The series owner can always mint"
+        );
+
+        let mut minters = self.minters_by_series.get(&id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::MintersBySeriesInner { series_id: id }
+                    .try_to_vec()
+                    .unwrap(),
+            )
+        });
+        require!(minters.insert(&account_id), "Already a minter");
+        self.minters_by_series.insert(&id, &minters);
+
+        let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
+        refund_deposit(required_storage_in_bytes);
+    }
+
+    /// Stop an account from minting tokens of a series. The freed storage is refunded to the series owner.
+    #[payable]
+    pub fn remove_series_minter(&mut self, id: u64, account_id: AccountId) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let series = self.series_by_id.get(&id).expect("Not a series");
+        assert_eq!(
+            env::predecessor_account_id(),
+            series.owner_id,
+            "Only the series owner can remove minters"
+        );
+
+        let mut minters = self.minters_by_series.get(&id).expect("No minters");
+        require!(minters.remove(&account_id), "Not a minter");
+        if minters.is_empty() {
+            self.minters_by_series.remove(&id);
+        } else {
+            self.minters_by_series.insert(&id, &minters);
+        }
+
+        refund_freed_storage(series.owner_id, initial_storage_usage);
+    }
+
+    //whether an account can mint tokens of a series, either as its owner or as an approved minter
+    pub fn is_series_minter(&self, id: u64, account_id: AccountId) -> bool {
+        let series = if let Some(series) = self.series_by_id.get(&id) {
+            series
+        } else {
+            return false;
+        };
+
+        series.owner_id == account_id
+            || self
+                .minters_by_series
+                .get(&id)
+                .is_some_and(|minters| minters.contains(&account_id))
+    }
+
+    //paginate through the minters approved by the owner of a series
+    pub fn get_series_minters(
+        &self,
+        id: u64,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<AccountId> {
+        let minters = if let Some(minters) = self.minters_by_series.get(&id) {
+            minters
+        } else {
+            return vec![];
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        minters
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+}
+
+impl Contract {
+    //panic unless the caller can mint tokens of the series
+    pub(crate) fn internal_assert_can_mint(&self, series_id: SeriesId) {
+        assert!(
+            self.is_series_minter(series_id, env::predecessor_account_id()),
+            "Only the series owner and approved minters can mint"
+        );
+    }
+}
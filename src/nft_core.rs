@@ -1,6 +1,9 @@
 use crate::*;
 use near_sdk::{assert_one_yocto, ext_contract, PromiseResult};
 
+//the maximum number of tokens that can be transferred in a single batch (this is limited by GAS)
+const MAX_BATCH_TRANSFER: usize = 100;
+
 pub trait NonFungibleTokenCore {
     fn nft_transfer(
         &mut self,
@@ -32,6 +35,25 @@ trait NonFungibleTokenReceiver {
     ) -> Promise;
 }
 
+//what a transfer call holds on to until it resolves: the approvals the token had before, restored if the
+//receiver sends the token back, and the storage deposit taken up front for recording the token going back
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingTransferCall {
+    pub approved_account_ids: HashMap<AccountId, u64>,
+    pub approval_expires_at: HashMap<AccountId, u64>,
+    pub provenance_deposit: U128,
+}
+
+impl PendingTransferCall {
+    //every account whose approval or expiry takes storage
+    pub fn account_ids(&self) -> impl Iterator<Item = &AccountId> {
+        self.approved_account_ids
+            .keys()
+            .chain(self.approval_expires_at.keys())
+    }
+}
+
 #[ext_contract(ext_self)]
 trait NonFungibleTokenResolver {
     fn nft_resolve_transfer(
@@ -40,7 +62,7 @@ trait NonFungibleTokenResolver {
         owner_id: AccountId,
         receiver_id: AccountId,
         token_id: TokenId,
-        approved_account_ids: HashMap<AccountId, u64>,
+        pending: PendingTransferCall,
         memo: Option<String>,
     ) -> bool;
 }
@@ -59,13 +81,16 @@ impl NonFungibleTokenCore for Contract {
 
         let sender_id = env::predecessor_account_id();
 
-        let previous_token =
-            self.internal_transfer(&sender_id, &receiver_id, &token_id, approval_id, memo);
-
-        refund_approved_account_ids(
-            previous_token.owner_id.clone(),
-            &previous_token.approved_account_ids,
+        let (previous_token, _) = self.internal_transfer(
+            &sender_id,
+            &receiver_id,
+            &token_id,
+            approval_id,
+            memo,
+            false,
         );
+
+        refund_token_approvals(previous_token.owner_id.clone(), &previous_token);
     }
 
     #[payable]
@@ -81,14 +106,19 @@ impl NonFungibleTokenCore for Contract {
 
         let sender_id = env::predecessor_account_id();
 
-        let previous_token = self.internal_transfer(
+        let (previous_token, children) = self.internal_transfer(
             &sender_id,
             &receiver_id,
             &token_id,
             approval_id,
             memo.clone(),
+            false,
         );
 
+        //the provenance recorded if the receiver sends the tokens back is paid up front by the sender
+        let provenance_deposit = STORAGE_PER_PROVENANCE_ENTRY * (children.len() as u128 + 1);
+        self.internal_take_storage_deposit(&sender_id, provenance_deposit);
+
         let mut authorized_id = None;
         //if the sender isn't the owner of the token, we set the authorized ID equal to the sender.
         if sender_id != previous_token.owner_id {
@@ -111,7 +141,11 @@ impl NonFungibleTokenCore for Contract {
                         previous_token.owner_id,
                         receiver_id,
                         token_id,
-                        previous_token.approved_account_ids,
+                        PendingTransferCall {
+                            approved_account_ids: previous_token.approved_account_ids,
+                            approval_expires_at: previous_token.approval_expires_at,
+                            provenance_deposit: U128(provenance_deposit),
+                        },
                         memo,
                     ),
             )
@@ -121,6 +155,7 @@ impl NonFungibleTokenCore for Contract {
     fn nft_token(&self, token_id: TokenId) -> Option<JsonToken> {
         if let Some(token) = self.token_by_id.get(&token_id) {
             let metadata = self.token_metadata_by_id.get(&token_id).unwrap();
+            let royalty = self.internal_token_royalty(&token);
 
             Some(JsonToken {
                 series_id: token.series_id,
@@ -128,7 +163,7 @@ impl NonFungibleTokenCore for Contract {
                 owner_id: token.owner_id,
                 metadata,
                 approved_account_ids: token.approved_account_ids,
-                royalty: token.royalty,
+                royalty,
             })
         } else {
             None
@@ -136,6 +171,91 @@ impl NonFungibleTokenCore for Contract {
     }
 }
 
+#[near_bindgen]
+impl Contract {
+    /// Transfer several tokens in one call. Each entry is the token ID, its receiver and an optional approval ID.
+    /// A single transfer event is logged containing every token ID.
+    #[payable]
+    pub fn nft_batch_transfer(
+        &mut self,
+        tokens: Vec<(TokenId, AccountId, Option<u64>)>,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        assert!(!tokens.is_empty(), "No tokens to transfer");
+        assert!(
+            tokens.len() <= MAX_BATCH_TRANSFER,
+            "Cannot transfer more than {} tokens at once",
+            MAX_BATCH_TRANSFER
+        );
+
+        let sender_id = env::predecessor_account_id();
+
+        //group the transferred token IDs per old owner and receiver for the event
+        let mut transfer_logs: Vec<NftTransferLog> = Vec::new();
+
+        for (token_id, receiver_id, approval_id) in tokens {
+            let (previous_token, children) =
+                self.internal_move_token(&sender_id, &receiver_id, &token_id, approval_id);
+
+            //record the new owner of the token and of everything nested in it, paid by the sender
+            for moved_token_id in [vec![token_id.clone()], children.clone()].concat() {
+                self.internal_record_provenance(
+                    &moved_token_id,
+                    &receiver_id,
+                    &memo,
+                    false,
+                    Some(&sender_id),
+                );
+            }
+
+            refund_token_approvals(previous_token.owner_id.clone(), &previous_token);
+
+            //if the sender isn't the owner of the token, we set the authorized ID equal to the sender.
+            let authorized_id = if sender_id != previous_token.owner_id {
+                Some(sender_id.to_string())
+            } else {
+                None
+            };
+            let old_owner_id = previous_token.owner_id.to_string();
+            let new_owner_id = receiver_id.to_string();
+
+            if let Some(log) = transfer_logs.iter_mut().find(|log| {
+                log.old_owner_id == old_owner_id
+                    && log.new_owner_id == new_owner_id
+                    && log.authorized_id == authorized_id
+            }) {
+                log.token_ids.push(token_id);
+                log.token_ids.extend(children);
+            } else {
+                transfer_logs.push(NftTransferLog {
+                    authorized_id,
+                    old_owner_id,
+                    new_owner_id,
+                    token_ids: [vec![token_id], children].concat(),
+                    memo: memo.clone(),
+                });
+            }
+        }
+
+        if let Some(memo) = memo.as_ref() {
+            env::log_str(&format!("memo: {}", memo));
+        }
+
+        let nft_transfer_log: EventLog = EventLog {
+            // Standard name ("nep171").
+            standard: NFT_STANDARD_NAME.to_string(),
+            // Version of the standard ("nft-1.0.0").
+            version: NFT_METADATA_SPEC.to_string(),
+            // One entry per old owner and receiver, each with the token IDs that moved between them.
+            event: EventLogVariant::NftTransfer(transfer_logs),
+        };
+
+        // Log the serialized json.
+        env::log_str(&nft_transfer_log.to_string());
+    }
+}
+
 #[near_bindgen]
 impl NonFungibleTokenResolver for Contract {
     #[private]
@@ -145,13 +265,20 @@ impl NonFungibleTokenResolver for Contract {
         owner_id: AccountId,
         receiver_id: AccountId,
         token_id: TokenId,
-        approved_account_ids: HashMap<AccountId, u64>,
+        pending: PendingTransferCall,
         memo: Option<String>,
     ) -> bool {
+        //the account that started the transfer paid up front for the provenance of the tokens going back
+        let payer_id = authorized_id
+            .as_ref()
+            .and_then(|authorized_id| authorized_id.parse::<AccountId>().ok())
+            .unwrap_or_else(|| owner_id.clone());
+
         if let PromiseResult::Successful(value) = env::promise_result(0) {
             if let Ok(return_token) = near_sdk::serde_json::from_slice::<bool>(&value) {
                 if !return_token {
-                    refund_approved_account_ids(owner_id, &approved_account_ids);
+                    refund_approved_account_ids_iter(owner_id, pending.account_ids());
+                    self.internal_return_storage_deposit(&payer_id, pending.provenance_deposit.0);
                     return true;
                 }
             }
@@ -159,25 +286,54 @@ impl NonFungibleTokenResolver for Contract {
 
         let mut token = if let Some(token) = self.token_by_id.get(&token_id) {
             if token.owner_id != receiver_id {
-                refund_approved_account_ids(owner_id, &approved_account_ids);
+                refund_approved_account_ids_iter(owner_id, pending.account_ids());
+                self.internal_return_storage_deposit(&payer_id, pending.provenance_deposit.0);
                 return true;
             }
             token
         } else {
-            refund_approved_account_ids(owner_id, &approved_account_ids);
+            refund_approved_account_ids_iter(owner_id, pending.account_ids());
+            self.internal_return_storage_deposit(&payer_id, pending.provenance_deposit.0);
             return true;
         };
 
-        self.internal_remove_token_from_owner(&receiver_id.clone(), &token_id);
-        self.internal_add_token_to_owner(&owner_id, &token_id);
+        //tokens nested by the receiver meanwhile would go back without their provenance paid for, so the receiver keeps them
+        let returned_token_count = 1 + self.internal_descendant_count(&token_id);
+        if STORAGE_PER_PROVENANCE_ENTRY * u128::from(returned_token_count)
+            > pending.provenance_deposit.0
+        {
+            refund_approved_account_ids_iter(owner_id, pending.account_ids());
+            self.internal_return_storage_deposit(&payer_id, pending.provenance_deposit.0);
+            return true;
+        }
+
+        //the token takes back the storage it freed on the way out, so nothing is charged for it
+        self.internal_change_token_owner(&token_id, &receiver_id, &owner_id, None);
 
         token.owner_id = owner_id.clone();
 
-        refund_approved_account_ids(receiver_id.clone(), &token.approved_account_ids);
-        token.approved_account_ids = approved_account_ids;
+        refund_token_approvals(receiver_id.clone(), &token);
+        token.approved_account_ids = pending.approved_account_ids;
+        token.approval_expires_at = pending.approval_expires_at;
 
         self.token_by_id.insert(&token_id, &token);
 
+        //nested tokens go back along with their parent
+        let children = self.internal_move_children(&token_id, &receiver_id, &owner_id, None);
+
+        //record the token going back. Its storage was paid up front, whatever is left goes back to the payer
+        let initial_storage_usage = env::storage_usage();
+        for moved_token_id in [vec![token_id.clone()], children.clone()].concat() {
+            self.internal_record_provenance(&moved_token_id, &owner_id, &memo, false, None);
+        }
+        let used_deposit =
+            Balance::from(env::storage_usage().saturating_sub(initial_storage_usage))
+                * env::storage_byte_cost();
+        self.internal_return_storage_deposit(
+            &payer_id,
+            pending.provenance_deposit.0.saturating_sub(used_deposit),
+        );
+
         let nft_transfer_log: EventLog = EventLog {
             // Standard name ("nep171").
             standard: NFT_STANDARD_NAME.to_string(),
@@ -191,8 +347,8 @@ impl NonFungibleTokenResolver for Contract {
                 old_owner_id: receiver_id.to_string(),
                 // The account ID of the new owner of the token.
                 new_owner_id: owner_id.to_string(),
-                // A vector containing the token IDs as strings.
-                token_ids: vec![token_id.to_string()],
+                // A vector containing the token IDs as strings, nested tokens included.
+                token_ids: [vec![token_id.to_string()], children].concat(),
                 // An optional memo to include.
                 memo,
             }]),
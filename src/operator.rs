@@ -0,0 +1,103 @@
+use near_sdk::assert_one_yocto;
+
+use crate::*;
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonOperator {
+    pub operator_id: AccountId,
+    // The series the operator is limited to, or None for every token of the owner
+    pub series_id: Option<SeriesId>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Allow an operator to transfer any of the caller's tokens, or only those of `series_id`.
+    /// Approving the same operator again replaces its scope.
+    #[payable]
+    pub fn nft_approve_operator(&mut self, operator_id: AccountId, series_id: Option<SeriesId>) {
+        assert_at_least_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let owner_id = env::predecessor_account_id();
+        require!(owner_id != operator_id, "Can't approve yourself");
+        if let Some(series_id) = series_id {
+            require!(self.series_by_id.get(&series_id).is_some(), "Not a series");
+        }
+
+        let mut operators = self.operators_by_owner.get(&owner_id).unwrap_or_else(|| {
+            UnorderedMap::new(
+                StorageKey::OperatorsByOwnerInner {
+                    account_id_hash: hash_account_id(&owner_id),
+                }
+                .try_to_vec()
+                .unwrap(),
+            )
+        });
+        operators.insert(&operator_id, &series_id);
+        self.operators_by_owner.insert(&owner_id, &operators);
+
+        let required_storage_in_bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+        refund_deposit(required_storage_in_bytes);
+    }
+
+    /// Revoke an operator. The freed storage is refunded to the caller.
+    #[payable]
+    pub fn nft_revoke_operator(&mut self, operator_id: AccountId) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let owner_id = env::predecessor_account_id();
+        let mut operators = self
+            .operators_by_owner
+            .get(&owner_id)
+            .expect("No operators");
+        require!(operators.remove(&operator_id).is_some(), "Not an operator");
+        if operators.is_empty() {
+            self.operators_by_owner.remove(&owner_id);
+        } else {
+            self.operators_by_owner.insert(&owner_id, &operators);
+        }
+
+        refund_freed_storage(owner_id, initial_storage_usage);
+    }
+
+    //whether an operator can transfer the owner's tokens. Pass a series ID to check a token of that series
+    pub fn nft_is_operator(
+        &self,
+        owner_id: AccountId,
+        operator_id: AccountId,
+        series_id: Option<SeriesId>,
+    ) -> bool {
+        self.operators_by_owner
+            .get(&owner_id)
+            .and_then(|operators| operators.get(&operator_id))
+            .is_some_and(|scope| scope.is_none() || scope == series_id)
+    }
+
+    //paginate through the operators an owner has approved
+    pub fn nft_operators(
+        &self,
+        owner_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonOperator> {
+        let operators = if let Some(operators) = self.operators_by_owner.get(&owner_id) {
+            operators
+        } else {
+            return vec![];
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        operators
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|(operator_id, series_id)| JsonOperator {
+                operator_id,
+                series_id,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,100 @@
+use near_sdk::{collections::Vector, json_types::U64};
+
+use crate::*;
+
+//the longest memo that is kept with an ownership change
+pub(crate) const MAX_PROVENANCE_MEMO_LEN: usize = 256;
+//the storage taken from the payer for an ownership change that is recorded later (e.g. when a token is sent back)
+pub(crate) const STORAGE_PER_PROVENANCE_ENTRY: u128 = 500 * STORAGE_PRICE_PER_BYTE;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProvenanceEntry {
+    // Owner of the token after the change
+    pub owner_id: AccountId,
+    // When the change happened (unix epoch in milliseconds)
+    pub timestamp: U64,
+    // Memo passed with the transfer
+    pub memo: Option<String>,
+    // Whether the token changed hands through a sale
+    pub is_sale: bool,
+}
+
+impl Contract {
+    /// Append an ownership change to the provenance of a token. The storage is taken from the payer's
+    /// storage deposit and the call panics if the deposit can't cover it. Without a payer the caller
+    /// already accounts for the storage (e.g. when minting, or with storage paid up front).
+    pub(crate) fn internal_record_provenance(
+        &mut self,
+        token_id: &TokenId,
+        owner_id: &AccountId,
+        memo: &Option<String>,
+        is_sale: bool,
+        payer_id: Option<&AccountId>,
+    ) {
+        require!(
+            memo.as_ref()
+                .is_none_or(|memo| memo.len() <= MAX_PROVENANCE_MEMO_LEN),
+            format!("Memo is longer than {} bytes", MAX_PROVENANCE_MEMO_LEN)
+        );
+
+        let initial_storage_usage = env::storage_usage();
+
+        let mut provenance = self.provenance_by_token.get(token_id).unwrap_or_else(|| {
+            Vector::new(
+                StorageKey::ProvenanceByTokenInner {
+                    token_id_hash: hash_token_id(token_id),
+                }
+                .try_to_vec()
+                .unwrap(),
+            )
+        });
+        provenance.push(&ProvenanceEntry {
+            owner_id: owner_id.clone(),
+            timestamp: U64(env::block_timestamp_ms()),
+            memo: memo.clone(),
+            is_sale,
+        });
+        self.provenance_by_token.insert(token_id, &provenance);
+
+        if let Some(payer_id) = payer_id {
+            let required_cost =
+                Balance::from(env::storage_usage().saturating_sub(initial_storage_usage))
+                    * env::storage_byte_cost();
+            self.internal_take_storage_deposit(payer_id, required_cost);
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    //get the number of ownership changes recorded for a token
+    pub fn nft_provenance_supply(&self, token_id: TokenId) -> U64 {
+        U64(self
+            .provenance_by_token
+            .get(&token_id)
+            .map_or(0, |provenance| provenance.len()))
+    }
+
+    //paginate through the ownership changes of a token, oldest first
+    pub fn nft_provenance(
+        &self,
+        token_id: TokenId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<ProvenanceEntry> {
+        let provenance = if let Some(provenance) = self.provenance_by_token.get(&token_id) {
+            provenance
+        } else {
+            return vec![];
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        provenance
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+}
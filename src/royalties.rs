@@ -19,13 +19,13 @@ impl NonFungibleTokenMe for Contract {
     fn nft_payout(&self, token_id: TokenId, balance: u128, max_len_payout: u32) -> Payout {
         let token = self.token_by_id.get(&token_id).expect("No token");
 
+        let royalty = self.internal_token_royalty(&token);
         let owner_id = token.owner_id;
         let mut total_perpetual = 0;
         let balance_u128 = u128::from(balance);
         let mut payout_object = Payout {
             payout: HashMap::new(),
         };
-        let royalty = token.royalty;
 
         assert!(
             royalty.len() as u32 <= max_len_payout,
@@ -63,15 +63,18 @@ impl NonFungibleTokenMe for Contract {
 
         let sender_id = env::predecessor_account_id();
 
-        let previous_token =
-            self.internal_transfer(&sender_id, &receiver_id, &token_id, Some(approval_id), memo);
-
-        refund_approved_account_ids(
-            previous_token.owner_id.clone(),
-            &previous_token.approved_account_ids,
+        let (previous_token, _) = self.internal_transfer(
+            &sender_id,
+            &receiver_id,
+            &token_id,
+            Some(approval_id),
+            memo,
+            true,
         );
 
-        let owner_id = previous_token.owner_id;
+        refund_token_approvals(previous_token.owner_id.clone(), &previous_token);
+
+        let owner_id = previous_token.owner_id.clone();
 
         let mut total_perpetual = 0;
 
@@ -81,7 +84,7 @@ impl NonFungibleTokenMe for Contract {
             payout: HashMap::new(),
         };
 
-        let royalty = previous_token.royalty;
+        let royalty = self.internal_token_royalty(&previous_token);
 
         assert!(
             royalty.len() as u32 <= max_len_payout,
@@ -106,3 +109,190 @@ impl NonFungibleTokenMe for Contract {
         payout_object
     }
 }
+
+#[near_bindgen]
+impl Contract {
+    /// Replace a royalty recipient for a series and all of its tokens. The new recipient is applied lazily
+    /// when payouts are computed; `migrate_series_royalties` can be used to write it into the stored tokens.
+    /// Percentages can only be lowered through `max_percentage`, never raised.
+    #[payable]
+    pub fn update_series_royalty(
+        &mut self,
+        id: u64,
+        account_id: AccountId,
+        new_account_id: AccountId,
+        max_percentage: Option<u32>,
+    ) {
+        assert_at_least_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let mut series = self.series_by_id.get(&id).expect("Not a series");
+        require!(
+            env::predecessor_account_id() == series.owner_id,
+            "Only the series owner can update royalties"
+        );
+        self.internal_redirect_series_royalty(
+            id,
+            &mut series,
+            account_id,
+            new_account_id,
+            max_percentage,
+        );
+
+        //a redirect can replace a longer one, charge for growth and refund what was freed up
+        let required_storage_in_bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+        refund_deposit(required_storage_in_bytes);
+        refund_freed_storage(series.owner_id, initial_storage_usage);
+    }
+
+    /// Paginate through the tokens of a series and write the redirected royalties into each stored token.
+    #[payable]
+    pub fn migrate_series_royalties(
+        &mut self,
+        id: u64,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) {
+        assert_at_least_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let series = self.series_by_id.get(&id).expect("Not a series");
+        require!(
+            env::predecessor_account_id() == series.owner_id,
+            "Only the series owner can update royalties"
+        );
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+        let token_ids: Vec<TokenId> = series
+            .tokens
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect();
+
+        let mut updated = Vec::new();
+        for token_id in token_ids {
+            if let Some(mut token) = self.token_by_id.get(&token_id) {
+                let royalty = apply_royalty_redirects(&token.royalty, &series.royalty_redirects);
+                if royalty != token.royalty {
+                    token.royalty = royalty;
+                    self.token_by_id.insert(&token_id, &token);
+                    updated.push(token_id);
+                }
+            }
+        }
+
+        if !updated.is_empty() {
+            let royalty_update_log: EventLog = EventLog {
+                standard: NFT_STANDARD_NAME.to_string(),
+                version: NFT_METADATA_SPEC.to_string(),
+                event: EventLogVariant::NftRoyaltyUpdate(
+                    series
+                        .royalty_redirects
+                        .iter()
+                        .map(|(account_id, redirect)| NftRoyaltyUpdateLog {
+                            series_id: id.to_string(),
+                            old_account_id: account_id.to_string(),
+                            new_account_id: redirect.account_id.to_string(),
+                            max_percentage: redirect.max_percentage,
+                            token_ids: Some(updated.clone()),
+                        })
+                        .collect(),
+                ),
+            };
+            env::log_str(&royalty_update_log.to_string());
+        }
+
+        //rewritten royalties can be larger or smaller than before, charge for growth and refund what was freed up
+        let required_storage_in_bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+        refund_deposit(required_storage_in_bytes);
+        refund_freed_storage(series.owner_id, initial_storage_usage);
+    }
+
+    //get the royalty redirects that are applied to every token of a series
+    pub fn get_series_royalty_redirects(&self, id: u64) -> HashMap<AccountId, RoyaltyRedirect> {
+        self.series_by_id
+            .get(&id)
+            .map(|series| series.royalty_redirects)
+            .unwrap_or_default()
+    }
+}
+
+impl Contract {
+    //redirect the royalties of an account on a series, write the series back and log the change
+    pub(crate) fn internal_redirect_series_royalty(
+        &mut self,
+        id: u64,
+        series: &mut Series,
+        account_id: AccountId,
+        new_account_id: AccountId,
+        max_percentage: Option<u32>,
+    ) {
+        require!(
+            account_id != new_account_id || max_percentage.is_some(),
+            "Nothing to update"
+        );
+        if let Some(max_percentage) = max_percentage {
+            require!(max_percentage <= 10000, "Royalty cannot exceed 100%");
+        }
+
+        //an account that was already redirected elsewhere no longer receives royalties
+        if let Some(redirect) = series.royalty_redirects.get(&account_id) {
+            require!(
+                redirect.account_id == account_id,
+                "Account is no longer a royalty recipient"
+            );
+        }
+        //only a single hop is kept, so the new recipient can't itself be redirected
+        if let Some(redirect) = series.royalty_redirects.get(&new_account_id) {
+            require!(
+                redirect.account_id == new_account_id,
+                "New account is already redirected"
+            );
+        }
+
+        //move every earlier redirect pointing at the account over to the new account
+        for redirect in series.royalty_redirects.values_mut() {
+            if redirect.account_id == account_id {
+                redirect.account_id = new_account_id.clone();
+                redirect.max_percentage = match (redirect.max_percentage, max_percentage) {
+                    (Some(current), Some(max)) => Some(current.min(max)),
+                    (current, max) => current.or(max),
+                };
+            }
+        }
+        let max_for_account = series
+            .royalty_redirects
+            .get(&account_id)
+            .and_then(|redirect| redirect.max_percentage);
+        series.royalty_redirects.insert(
+            account_id.clone(),
+            RoyaltyRedirect {
+                account_id: new_account_id.clone(),
+                max_percentage: match (max_for_account, max_percentage) {
+                    (Some(current), Some(max)) => Some(current.min(max)),
+                    (current, max) => current.or(max),
+                },
+            },
+        );
+
+        //keep the series level royalty in sync as well
+        if let Some(royalty) = series.royalty.as_ref() {
+            series.royalty = Some(apply_royalty_redirects(royalty, &series.royalty_redirects));
+        }
+
+        let royalty_update_log: EventLog = EventLog {
+            standard: NFT_STANDARD_NAME.to_string(),
+            version: NFT_METADATA_SPEC.to_string(),
+            event: EventLogVariant::NftRoyaltyUpdate(vec![NftRoyaltyUpdateLog {
+                series_id: id.to_string(),
+                old_account_id: account_id.to_string(),
+                new_account_id: new_account_id.to_string(),
+                max_percentage,
+                token_ids: None,
+            }]),
+        };
+        env::log_str(&royalty_update_log.to_string());
+        self.series_by_id.insert(&id, series);
+    }
+}
@@ -4,6 +4,8 @@ use crate::*;
 
 const GAS_FOR_RESOLVE_PURCHASE: Gas = Gas(115_000_000_000_000);
 const GAS_FOR_NFT_TRANSFER: Gas = Gas(15_000_000_000_000);
+const GAS_FOR_NFT_IS_APPROVED: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_RESOLVE_OFFER: Gas = Gas(155_000_000_000_000);
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
@@ -58,6 +60,12 @@ impl Contract {
             "Must be sale owner"
         );
 
+        //the listing moves to the new price in the series stats, covered by the storage reserved for the sale
+        if contract_id == env::current_account_id() {
+            self.internal_stats_remove_listing(&token_id, sale.sale_conditions.0);
+            self.internal_stats_add_listing(&token_id, price.0);
+        }
+
         //set the sale conditions equal to the passed in price
         sale.sale_conditions = price;
         //insert the sale back into the map for the unique sale ID
@@ -92,8 +100,69 @@ impl Contract {
             price
         );
 
-        //process the purchase (which will remove the sale, transfer and get the payout from the nft contract, and then distribute royalties)
-        self.process_purchase(contract_id, token_id, U128(deposit), buyer_id);
+        //tokens minted on this contract can be checked right away. If the listing went stale we prune it and refund the buyer
+        if contract_id == env::current_account_id() {
+            if !self.internal_is_native_sale_valid(&sale) {
+                self.internal_prune_sale(contract_id, token_id, buyer_id, deposit);
+                return;
+            }
+
+            //process the purchase (which will remove the sale, transfer and get the payout from the nft contract, and then distribute royalties)
+            self.process_purchase(contract_id, token_id, U128(deposit), buyer_id);
+            return;
+        }
+
+        //for other contracts, make sure the market's approval is still valid before processing the purchase
+        ext_contract::ext(contract_id.clone())
+            .with_static_gas(GAS_FOR_NFT_IS_APPROVED)
+            .nft_is_approved(
+                token_id.clone(),
+                env::current_account_id(),
+                Some(sale.approval_id),
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_OFFER)
+                    .resolve_offer(
+                        contract_id,
+                        token_id,
+                        sale.approval_id,
+                        U128(deposit),
+                        buyer_id,
+                    ),
+            );
+    }
+
+    #[private]
+    pub fn resolve_offer(
+        &mut self,
+        nft_contract_id: AccountId,
+        token_id: String,
+        approval_id: u64,
+        price: U128,
+        buyer_id: AccountId,
+    ) {
+        //the approval is only valid if the nft contract answered true
+        let is_approved = promise_result_as_success()
+            .and_then(|value| near_sdk::serde_json::from_slice::<bool>(&value).ok())
+            .unwrap_or(false);
+
+        //the sale could have been removed or relisted while we were waiting, in that case we just refund the buyer
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, DELIMETER, token_id);
+        match self.sales.get(&contract_and_token_id) {
+            Some(sale) if sale.approval_id == approval_id => {}
+            _ => {
+                Promise::new(buyer_id).transfer(price.0);
+                return;
+            }
+        }
+
+        if !is_approved {
+            self.internal_prune_sale(nft_contract_id, token_id, buyer_id, price.0);
+            return;
+        }
+
+        self.process_purchase(nft_contract_id, token_id, price, buyer_id);
     }
 
     #[private]
@@ -106,6 +175,8 @@ impl Contract {
     ) -> Promise {
         //get the sale object by removing the sale
         let sale = self.internal_remove_sale(nft_contract_id.clone(), token_id.clone());
+        let nft_contract_id_for_earnings = nft_contract_id.clone();
+        let token_id_for_earnings = token_id.clone();
 
         //initiate a cross contract call to the nft contract. This will transfer the token to the buyer and return
         //a payout object used for the market to distribute funds to the appropriate accounts.
@@ -134,12 +205,22 @@ impl Contract {
                     .resolve_purchase(
                         buyer_id, //the buyer and price are passed in incase something goes wrong and we need to refund the buyer
                         price,
+                        sale.owner_id, //the seller, token and contract are used to record creator earnings
+                        nft_contract_id_for_earnings,
+                        token_id_for_earnings,
                     ),
             )
     }
 
     #[private]
-    pub fn resolve_purchase(&mut self, buyer_id: AccountId, price: U128) -> U128 {
+    pub fn resolve_purchase(
+        &mut self,
+        buyer_id: AccountId,
+        price: U128,
+        seller_id: AccountId,
+        nft_contract_id: AccountId,
+        token_id: String,
+    ) -> U128 {
         // checking for payout information returned from the nft_transfer_payout method
         let payout_option = promise_result_as_success().and_then(|value| {
             //if we set the payout_option to None, that means something went wrong and we should refund the buyer
@@ -187,6 +268,17 @@ impl Contract {
             return price;
         };
 
+        //only tokens minted on this contract belong to a series
+        let series_id = if nft_contract_id == env::current_account_id() {
+            self.token_by_id.get(&token_id).map(|token| token.series_id)
+        } else {
+            None
+        };
+        self.internal_record_earnings(series_id, &seller_id, &payout);
+        if let Some(series_id) = series_id {
+            self.internal_stats_record_sale(series_id, &token_id, price.0);
+        }
+
         // NEAR payouts
         for (receiver_id, amount) in payout {
             Promise::new(receiver_id).transfer(amount.0);
@@ -197,7 +289,54 @@ impl Contract {
     }
 }
 
+impl Contract {
+    //check that a sale of a token minted on this contract is still backed by the owner's approval
+    pub(crate) fn internal_is_native_sale_valid(&self, sale: &Sale) -> bool {
+        self.token_by_id
+            .get(&sale.token_id)
+            .map(|token| {
+                token.owner_id == sale.owner_id
+                    && !token
+                        .is_approval_expired(&env::current_account_id(), env::block_timestamp_ms())
+                    && token.approved_account_ids.get(&env::current_account_id())
+                        == Some(&sale.approval_id)
+            })
+            .unwrap_or(false)
+    }
+
+    //remove a stale sale and give the buyer their deposit back. The seller's storage deposit is freed up again
+    pub(crate) fn internal_prune_sale(
+        &mut self,
+        nft_contract_id: AccountId,
+        token_id: String,
+        buyer_id: AccountId,
+        deposit: Balance,
+    ) {
+        self.internal_remove_sale(nft_contract_id, token_id.clone());
+        env::log_str(&format!(
+            "Sale for {} is no longer valid and was removed",
+            token_id
+        ));
+        Promise::new(buyer_id).transfer(deposit);
+    }
+}
+
 #[ext_contract(ext_self)]
 trait ExtSelf {
-    fn resolve_purchase(&mut self, buyer_id: AccountId, price: U128) -> Promise;
+    fn resolve_offer(
+        &mut self,
+        nft_contract_id: AccountId,
+        token_id: String,
+        approval_id: u64,
+        price: U128,
+        buyer_id: AccountId,
+    );
+    fn resolve_purchase(
+        &mut self,
+        buyer_id: AccountId,
+        price: U128,
+        seller_id: AccountId,
+        nft_contract_id: AccountId,
+        token_id: String,
+    ) -> Promise;
 }
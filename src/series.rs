@@ -7,11 +7,27 @@ impl Contract {
         self.series_by_id.len()
     }
 
-    // Paginate through all the series on the contract and return the a vector of JsonSeries
-    pub fn get_series(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<JsonSeries> {
+    // Paginate through all the series on the contract and return the a vector of JsonSeries.
+    // Pass verified_only to only go through series verified by a curator
+    pub fn get_series(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+        verified_only: Option<bool>,
+    ) -> Vec<JsonSeries> {
         //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
         let start = u128::from(from_index.unwrap_or(U128(0)));
 
+        if verified_only.unwrap_or(false) {
+            return self
+                .verified_series
+                .iter()
+                .skip(start as usize)
+                .take(limit.unwrap_or(50) as usize)
+                .map(|series_id| self.get_series_details(series_id).unwrap())
+                .collect();
+        }
+
         //iterate through each series using an iterator
         self.series_by_id
             .keys()
@@ -25,12 +41,13 @@ impl Contract {
             .collect()
     }
 
-    //get series by owner
+    //get series by owner, optionally only the verified ones
     pub fn get_series_by_owner(
         &self,
         owner_id: AccountId,
         from_index: Option<U128>,
         limit: Option<u64>,
+        verified_only: Option<bool>,
     ) -> Vec<JsonSeries> {
         let series_for_owner_set = self.series_per_owner.get(&owner_id);
         let series = if let Some(series_for_owner_set) = series_for_owner_set {
@@ -41,8 +58,10 @@ impl Contract {
 
         let start = u128::from(from_index.unwrap_or(U128(0)));
 
+        let verified_only = verified_only.unwrap_or(false);
         series
             .iter()
+            .filter(|series_id| !verified_only || self.verified_series.contains(series_id))
             .skip(start as usize)
             .take(limit.unwrap_or(50) as usize)
             .map(|series_id| self.get_series_details(series_id.clone()).unwrap())
@@ -62,6 +81,16 @@ impl Contract {
                 owner_id: series.owner_id,
                 volume: series.volume,
                 price: series.price,
+                approved_can_burn: series.approved_can_burn,
+                soulbound: series.soulbound,
+                mutable_metadata: series.mutable_metadata,
+                expiry_enforced: series.expiry_enforced,
+                edition_token_ids: series.edition_token_ids,
+                copies: series.copies,
+                verified: series.verified,
+                frozen: series.frozen,
+                max_supply: series.max_supply,
+                minted_count: series.minted_count,
             })
         } else {
             //if there isn't a series, we'll return None
@@ -82,19 +111,75 @@ impl Contract {
         }
     }
 
+    //paginate through the series of a category, optionally only the verified ones
+    pub fn get_series_by_category(
+        &self,
+        category: String,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+        verified_only: Option<bool>,
+    ) -> Vec<JsonSeries> {
+        let series = if let Some(series) = self.series_by_category.get(&category) {
+            series
+        } else {
+            return vec![];
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        let verified_only = verified_only.unwrap_or(false);
+        series
+            .iter()
+            .filter(|series_id| !verified_only || self.verified_series.contains(series_id))
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|series_id| self.get_series_details(series_id).unwrap())
+            .collect()
+    }
+
+    //get how many more tokens can be minted for a series. None if the series has no limit
+    pub fn get_series_remaining_supply(&self, id: u64) -> Option<U128> {
+        let series = self.series_by_id.get(&id).expect("Not a series");
+        if series.frozen {
+            return Some(U128(0));
+        }
+
+        let remaining_copies = series
+            .copies
+            .map(|copies| copies.saturating_sub(series.minted_count));
+        let remaining_supply = series
+            .max_supply
+            .map(|max_supply| max_supply.saturating_sub(series.minted_count));
+        match (remaining_copies, remaining_supply) {
+            (Some(copies), Some(supply)) => Some(copies.min(supply)),
+            (remaining, None) | (None, remaining) => remaining,
+        }
+        .map(|remaining| U128(remaining as u128))
+    }
+
     #[payable]
-    pub fn create_series(&mut self, metadata: SeriesMetadata) {
+    pub fn create_series(&mut self, metadata: SeriesMetadata, options: Option<SeriesOptions>) {
         // Measure the initial storage being used on the contract
         let initial_storage_usage = env::storage_usage();
 
         // Ensure the caller is an approved creator
         let caller = env::predecessor_account_id();
         let owner = env::predecessor_account_id();
-        // require!(
-        //     self.approved_creators.contains(&caller) == true,
-        //     "only approved creators can add a type"
-        // );
+        let options = options.unwrap_or_default();
+        if let Some(copies) = options.copies {
+            require!(copies > 0, "copies must be greater than 0");
+        }
+        if let Some(max_supply) = options.max_supply {
+            require!(max_supply > 0, "max_supply must be greater than 0");
+        }
+        require!(
+            !self.curated_creation
+                || caller == self.owner_id
+                || self.approved_creators.contains(&caller),
+            "only approved creators can add a type"
+        );
         let id = self.get_series_total_supply() + 1;
+        let category = metadata.category.clone();
         // Insert the series and ensure it doesn't already exist
         require!(
             self.series_by_id
@@ -104,12 +189,23 @@ impl Contract {
                         metadata,
                         volume: None,
                         royalty: None,
-                        tokens: UnorderedSet::new(StorageKey::SeriesByIdInner {
-                            // We get a new unique prefix for the collection
-                            account_id_hash: hash_account_id(&caller),
+                        tokens: UnorderedSet::new(StorageKey::SeriesTokensInner {
+                            // Every series gets its own prefix for the collection
+                            series_id: id,
                         }),
                         owner_id: caller,
                         price: None,
+                        royalty_redirects: HashMap::new(),
+                        approved_can_burn: options.approved_can_burn,
+                        soulbound: options.soulbound,
+                        mutable_metadata: options.mutable_metadata,
+                        expiry_enforced: options.expiry_enforced,
+                        edition_token_ids: options.edition_token_ids,
+                        copies: options.copies,
+                        verified: false,
+                        frozen: false,
+                        max_supply: options.max_supply,
+                        minted_count: 0,
                     }
                 )
                 .is_none(),
@@ -117,6 +213,9 @@ impl Contract {
         );
 
         self.internal_add_series_to_owner(&owner, &id);
+        if let Some(category) = &category {
+            self.internal_add_series_to_category(category, &id);
+        }
 
         //calculate the required storage which was the used - initial
         let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
@@ -124,4 +223,53 @@ impl Contract {
         //refund any excess storage if the user attached too much. Panic if they didn't attach enough to cover the required.
         refund_deposit(required_storage_in_bytes);
     }
+
+    /// Permanently stop minting for a series. Only the series owner can do this and it can't be undone.
+    #[payable]
+    pub fn freeze_series(&mut self, id: u64) {
+        assert_one_yocto();
+
+        let mut series = self.series_by_id.get(&id).expect("Not a series");
+        require!(
+            env::predecessor_account_id() == series.owner_id,
+            "Only the series owner can freeze the series"
+        );
+        require!(!series.frozen, "Series is already frozen");
+
+        series.frozen = true;
+        self.series_by_id.insert(&id, &series);
+    }
+
+    /// Replace the metadata of a series. Only the series owner can do this. Extra storage is paid by
+    /// the owner and storage that is freed up is refunded.
+    #[payable]
+    pub fn update_series_metadata(&mut self, id: u64, metadata: SeriesMetadata) {
+        assert_at_least_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let mut series = self.series_by_id.get(&id).expect("Not a series");
+        let owner_id = env::predecessor_account_id();
+        require!(
+            owner_id == series.owner_id,
+            "Only the series owner can update the metadata"
+        );
+
+        //keep the category index in sync
+        if series.metadata.category != metadata.category {
+            if let Some(category) = &series.metadata.category {
+                self.internal_remove_series_from_category(category, &id);
+            }
+            if let Some(category) = &metadata.category {
+                self.internal_add_series_to_category(category, &id);
+            }
+        }
+
+        series.metadata = metadata;
+        self.series_by_id.insert(&id, &series);
+
+        //charge for growth, refund what was freed up
+        let required_storage_in_bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+        refund_deposit(required_storage_in_bytes);
+        refund_freed_storage(owner_id, initial_storage_usage);
+    }
 }
@@ -0,0 +1,142 @@
+use crate::*;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingSeriesTransfer {
+    // Account that can accept the series
+    pub new_owner_id: AccountId,
+    // Whether the royalties of the current owner move to the new owner on accept
+    pub transfer_royalties: bool,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Propose to hand a series over to another account, which takes effect once that account calls
+    /// `accept_series_transfer`. When `transfer_royalties` is set, the royalties paid to the current owner
+    /// are redirected to the new owner. A new proposal replaces the previous one.
+    #[payable]
+    pub fn propose_series_transfer(
+        &mut self,
+        id: u64,
+        new_owner_id: AccountId,
+        transfer_royalties: Option<bool>,
+    ) {
+        assert_at_least_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let series = self.series_by_id.get(&id).expect("Not a series");
+        assert_eq!(
+            env::predecessor_account_id(),
+            series.owner_id,
+            "Only the series owner can transfer the series"
+        );
+        require!(new_owner_id != series.owner_id, "Already the series owner");
+
+        self.pending_series_transfers.insert(
+            &id,
+            &PendingSeriesTransfer {
+                new_owner_id,
+                transfer_royalties: transfer_royalties.unwrap_or(false),
+            },
+        );
+
+        let required_storage_in_bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+        refund_deposit(required_storage_in_bytes);
+    }
+
+    /// Withdraw a pending series transfer. The freed storage is refunded to the series owner.
+    #[payable]
+    pub fn cancel_series_transfer(&mut self, id: u64) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let series = self.series_by_id.get(&id).expect("Not a series");
+        assert_eq!(
+            env::predecessor_account_id(),
+            series.owner_id,
+            "Only the series owner can cancel the transfer"
+        );
+        self.pending_series_transfers
+            .remove(&id)
+            .expect("No pending transfer");
+
+        refund_freed_storage(series.owner_id, initial_storage_usage);
+    }
+
+    /// Accept a series proposed to the caller. The caller becomes the series owner and pays for any
+    /// extra storage this needs. Minters approved by the previous owner are removed and their storage
+    /// is refunded to it. Royalties only move if neither owner was already redirected elsewhere.
+    #[payable]
+    pub fn accept_series_transfer(&mut self, id: u64) {
+        assert_at_least_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let mut series = self.series_by_id.get(&id).expect("Not a series");
+        let pending = self
+            .pending_series_transfers
+            .remove(&id)
+            .expect("No pending transfer");
+        let new_owner_id = env::predecessor_account_id();
+        assert_eq!(
+            new_owner_id, pending.new_owner_id,
+            "Only the proposed owner can accept the transfer"
+        );
+
+        let old_owner_id = series.owner_id.clone();
+        self.internal_remove_series_from_owner(&old_owner_id, &id);
+        self.internal_add_series_to_owner(&new_owner_id, &id);
+        series.owner_id = new_owner_id.clone();
+
+        //minters were approved by the old owner, they don't carry over to the new one
+        let minters_storage_usage = env::storage_usage();
+        if let Some(mut minters) = self.minters_by_series.remove(&id) {
+            minters.clear();
+        }
+        let freed_storage_in_bytes =
+            refund_freed_storage(old_owner_id.clone(), minters_storage_usage);
+
+        //royalties move along unless either owner was already redirected elsewhere
+        let is_royalty_recipient = |account_id: &AccountId| {
+            series
+                .royalty_redirects
+                .get(account_id)
+                .is_none_or(|redirect| &redirect.account_id == account_id)
+        };
+        let royalties_transferred = pending.transfer_royalties
+            && is_royalty_recipient(&old_owner_id)
+            && is_royalty_recipient(&new_owner_id);
+        if royalties_transferred {
+            self.internal_redirect_series_royalty(
+                id,
+                &mut series,
+                old_owner_id.clone(),
+                new_owner_id.clone(),
+                None,
+            );
+        } else {
+            self.series_by_id.insert(&id, &series);
+        }
+
+        let series_transfer_log: EventLog = EventLog {
+            standard: NFT_STANDARD_NAME.to_string(),
+            version: NFT_METADATA_SPEC.to_string(),
+            event: EventLogVariant::SeriesTransfer(vec![SeriesTransferLog {
+                series_id: id.to_string(),
+                old_owner_id: old_owner_id.to_string(),
+                new_owner_id: new_owner_id.to_string(),
+                royalties_transferred,
+            }]),
+        };
+        env::log_str(&series_transfer_log.to_string());
+
+        //the minters' storage was already refunded to the old owner, don't count it for the new one
+        let required_storage_in_bytes =
+            (env::storage_usage() + freed_storage_in_bytes).saturating_sub(initial_storage_usage);
+        refund_deposit(required_storage_in_bytes);
+    }
+
+    //get the pending transfer of a series, if there is one
+    pub fn get_pending_series_transfer(&self, id: u64) -> Option<PendingSeriesTransfer> {
+        self.pending_series_transfers.get(&id)
+    }
+}
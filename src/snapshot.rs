@@ -0,0 +1,261 @@
+use near_sdk::json_types::U64;
+
+use crate::*;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Snapshot {
+    // When the snapshot was taken (unix epoch in milliseconds)
+    pub timestamp: u64,
+    // Number of tokens of the series recorded so far
+    pub total_tokens: u64,
+    // How many tokens of the series each holder owned
+    pub holders: UnorderedMap<AccountId, u64>,
+    // Position in the token set of the series where the next page starts
+    pub next_index: u64,
+    // Whether every token of the series has been recorded
+    pub finalized: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonSnapshot {
+    pub series_id: SeriesId,
+    pub block_height: U64,
+    pub timestamp: U64,
+    pub total_tokens: U64,
+    pub holder_count: U64,
+    pub finalized: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonSnapshotHolder {
+    pub account_id: AccountId,
+    pub token_count: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Start recording who holds the tokens of a series at the current block height and record the first
+    /// `limit` tokens. Large series are finished in pages through `continue_snapshot`, and until then no tokens
+    /// of the series can be minted or burned. Only the series owner can take a snapshot and it pays for the
+    /// storage. Returns the block height the snapshot is stored under.
+    #[payable]
+    pub fn take_snapshot(&mut self, series_id: SeriesId, limit: Option<u64>) -> U64 {
+        let initial_storage_usage = env::storage_usage();
+
+        let series = self.series_by_id.get(&series_id).expect("Not a series");
+        assert_eq!(
+            env::predecessor_account_id(),
+            series.owner_id,
+            "Only the series owner can take a snapshot"
+        );
+        require!(
+            self.unfinished_snapshots.get(&series_id).is_none(),
+            "Another snapshot of the series is still being taken"
+        );
+
+        let block_height = env::block_height();
+        let mut snapshots = self.snapshots_by_series.get(&series_id).unwrap_or_else(|| {
+            UnorderedMap::new(
+                StorageKey::SnapshotsBySeriesInner { series_id }
+                    .try_to_vec()
+                    .unwrap(),
+            )
+        });
+        require!(
+            snapshots.get(&block_height).is_none(),
+            "A snapshot was already taken at this block height"
+        );
+
+        let mut snapshot = Snapshot {
+            timestamp: env::block_timestamp_ms(),
+            total_tokens: 0,
+            holders: UnorderedMap::new(
+                StorageKey::SnapshotHoldersInner {
+                    series_id,
+                    block_height,
+                }
+                .try_to_vec()
+                .unwrap(),
+            ),
+            next_index: 0,
+            finalized: false,
+        };
+        self.internal_record_snapshot_page(&series, &mut snapshot, limit);
+        if !snapshot.finalized {
+            self.unfinished_snapshots.insert(&series_id, &block_height);
+        }
+
+        snapshots.insert(&block_height, &snapshot);
+        self.snapshots_by_series.insert(&series_id, &snapshots);
+
+        let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
+        refund_deposit(required_storage_in_bytes);
+
+        U64(block_height)
+    }
+
+    /// Record the next `limit` tokens of a snapshot that isn't finalized yet. The tokens of the series are fixed
+    /// until then, but holdings are read when the page is recorded, not at the block height the snapshot was started. Only the series owner can do this and
+    /// it pays for the storage. Returns whether the snapshot is finalized.
+    #[payable]
+    pub fn continue_snapshot(
+        &mut self,
+        series_id: SeriesId,
+        block_height: U64,
+        limit: Option<u64>,
+    ) -> bool {
+        let initial_storage_usage = env::storage_usage();
+
+        let series = self.series_by_id.get(&series_id).expect("Not a series");
+        assert_eq!(
+            env::predecessor_account_id(),
+            series.owner_id,
+            "Only the series owner can take a snapshot"
+        );
+
+        let mut snapshots = self
+            .snapshots_by_series
+            .get(&series_id)
+            .expect("No snapshots");
+        let mut snapshot = snapshots.get(&block_height.0).expect("No snapshot");
+        require!(!snapshot.finalized, "Snapshot is already finalized");
+
+        self.internal_record_snapshot_page(&series, &mut snapshot, limit);
+        if snapshot.finalized {
+            self.unfinished_snapshots.remove(&series_id);
+        }
+        snapshots.insert(&block_height.0, &snapshot);
+
+        let required_storage_in_bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+        refund_deposit(required_storage_in_bytes);
+
+        snapshot.finalized
+    }
+
+    /// Delete a snapshot of a series. The freed storage is refunded to the series owner.
+    #[payable]
+    pub fn delete_snapshot(&mut self, series_id: SeriesId, block_height: U64) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let series = self.series_by_id.get(&series_id).expect("Not a series");
+        assert_eq!(
+            env::predecessor_account_id(),
+            series.owner_id,
+            "Only the series owner can delete a snapshot"
+        );
+
+        let mut snapshots = self
+            .snapshots_by_series
+            .get(&series_id)
+            .expect("No snapshots");
+        let mut snapshot = snapshots.remove(&block_height.0).expect("No snapshot");
+        snapshot.holders.clear();
+        if !snapshot.finalized {
+            self.unfinished_snapshots.remove(&series_id);
+        }
+        if snapshots.is_empty() {
+            self.snapshots_by_series.remove(&series_id);
+        } else {
+            self.snapshots_by_series.insert(&series_id, &snapshots);
+        }
+
+        refund_freed_storage(series.owner_id, initial_storage_usage);
+    }
+
+    //paginate through the snapshots of a series
+    pub fn get_snapshots(
+        &self,
+        series_id: SeriesId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonSnapshot> {
+        let snapshots = if let Some(snapshots) = self.snapshots_by_series.get(&series_id) {
+            snapshots
+        } else {
+            return vec![];
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        snapshots
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|(block_height, snapshot)| JsonSnapshot {
+                series_id,
+                block_height: U64(block_height),
+                timestamp: U64(snapshot.timestamp),
+                total_tokens: U64(snapshot.total_tokens),
+                holder_count: U64(snapshot.holders.len()),
+                finalized: snapshot.finalized,
+            })
+            .collect()
+    }
+
+    //paginate through the holders recorded in a snapshot
+    pub fn get_snapshot_holders(
+        &self,
+        series_id: SeriesId,
+        block_height: U64,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonSnapshotHolder> {
+        let snapshot = if let Some(snapshot) = self
+            .snapshots_by_series
+            .get(&series_id)
+            .and_then(|snapshots| snapshots.get(&block_height.0))
+        {
+            snapshot
+        } else {
+            return vec![];
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        snapshot
+            .holders
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|(account_id, token_count)| JsonSnapshotHolder {
+                account_id,
+                token_count: U64(token_count),
+            })
+            .collect()
+    }
+}
+
+impl Contract {
+    //panic while a snapshot of the series is being taken, its token set has to stay the same until it is finalized
+    pub(crate) fn internal_assert_no_unfinished_snapshot(&self, series_id: SeriesId) {
+        require!(
+            self.unfinished_snapshots.get(&series_id).is_none(),
+            "A snapshot of the series is being taken"
+        );
+    }
+
+    //count the owners of the next page of tokens of the series, finalizing the snapshot once every token is counted
+    pub(crate) fn internal_record_snapshot_page(
+        &self,
+        series: &Series,
+        snapshot: &mut Snapshot,
+        limit: Option<u64>,
+    ) {
+        let tokens = series.tokens.as_vector();
+        let end = tokens.len().min(snapshot.next_index + limit.unwrap_or(50));
+
+        for index in snapshot.next_index..end {
+            let token_id = tokens.get(index).unwrap();
+            let owner_id = self.token_by_id.get(&token_id).expect("No token").owner_id;
+            let count = snapshot.holders.get(&owner_id).unwrap_or(0);
+            snapshot.holders.insert(&owner_id, &(count + 1));
+            snapshot.total_tokens += 1;
+        }
+
+        snapshot.next_index = end.max(snapshot.next_index);
+        snapshot.finalized = snapshot.next_index >= tokens.len();
+    }
+}
@@ -0,0 +1,155 @@
+use near_sdk::{collections::TreeMap, json_types::U64};
+
+use crate::*;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LastSale {
+    pub token_id: TokenId,
+    pub price: U128,
+    // When the sale happened (unix epoch in milliseconds)
+    pub timestamp: U64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct SeriesStats {
+    // Number of accounts holding at least one token of the series
+    pub unique_holders: u64,
+    // Number of tokens of the series sold on the marketplace
+    pub sales_count: u64,
+    pub last_sale: Option<LastSale>,
+    // Active listings ordered by price, the first one is the floor
+    pub listings: TreeMap<(Balance, TokenId), ()>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonSeriesStats {
+    pub series_id: SeriesId,
+    pub floor_price: Option<U128>,
+    pub listed_count: U64,
+    pub unique_holders: U64,
+    pub total_supply: U64,
+    pub volume: U128,
+    pub sales_count: U64,
+    pub last_sale: Option<LastSale>,
+}
+
+#[near_bindgen]
+impl Contract {
+    //get the floor, listings, holders, supply and sales of a series in one call
+    pub fn get_series_stats(&self, series_id: SeriesId) -> Option<JsonSeriesStats> {
+        let series = self.series_by_id.get(&series_id)?;
+        let stats = self.internal_series_stats(series_id);
+
+        Some(JsonSeriesStats {
+            series_id,
+            floor_price: stats.listings.min().map(|(price, _)| U128(price)),
+            listed_count: U64(stats.listings.len()),
+            unique_holders: U64(stats.unique_holders),
+            total_supply: U64(series.tokens.len()),
+            volume: U128(series.volume.unwrap_or(0)),
+            sales_count: U64(stats.sales_count),
+            last_sale: stats.last_sale,
+        })
+    }
+}
+
+impl Contract {
+    //get the stats of a series, or empty stats if nothing was recorded yet
+    pub(crate) fn internal_series_stats(&self, series_id: SeriesId) -> SeriesStats {
+        self.stats_by_series
+            .get(&series_id)
+            .unwrap_or_else(|| SeriesStats {
+                unique_holders: 0,
+                sales_count: 0,
+                last_sale: None,
+                listings: TreeMap::new(
+                    StorageKey::SeriesListingsInner { series_id }
+                        .try_to_vec()
+                        .unwrap(),
+                ),
+            })
+    }
+
+    //count one more token of the series for the account
+    pub(crate) fn internal_stats_add_holder(
+        &mut self,
+        series_id: SeriesId,
+        account_id: &AccountId,
+    ) {
+        let key = (series_id, account_id.clone());
+        let count = self.holder_token_counts.get(&key).unwrap_or(0);
+        self.holder_token_counts.insert(&key, &(count + 1));
+
+        if count == 0 {
+            let mut stats = self.internal_series_stats(series_id);
+            stats.unique_holders += 1;
+            self.stats_by_series.insert(&series_id, &stats);
+        }
+    }
+
+    //count one less token of the series for the account
+    pub(crate) fn internal_stats_remove_holder(
+        &mut self,
+        series_id: SeriesId,
+        account_id: &AccountId,
+    ) {
+        let key = (series_id, account_id.clone());
+        let count = if let Some(count) = self.holder_token_counts.get(&key) {
+            count
+        } else {
+            return;
+        };
+
+        if count > 1 {
+            self.holder_token_counts.insert(&key, &(count - 1));
+        } else {
+            self.holder_token_counts.remove(&key);
+            let mut stats = self.internal_series_stats(series_id);
+            stats.unique_holders = stats.unique_holders.saturating_sub(1);
+            self.stats_by_series.insert(&series_id, &stats);
+        }
+    }
+
+    //track a listing of a token minted on this contract
+    pub(crate) fn internal_stats_add_listing(&mut self, token_id: &TokenId, price: Balance) {
+        if let Some(token) = self.token_by_id.get(token_id) {
+            let mut stats = self.internal_series_stats(token.series_id);
+            stats.listings.insert(&(price, token_id.clone()), &());
+            self.stats_by_series.insert(&token.series_id, &stats);
+        }
+    }
+
+    //stop tracking a listing of a token minted on this contract
+    pub(crate) fn internal_stats_remove_listing(&mut self, token_id: &TokenId, price: Balance) {
+        if let Some(token) = self.token_by_id.get(token_id) {
+            if let Some(mut stats) = self.stats_by_series.get(&token.series_id) {
+                stats.listings.remove(&(price, token_id.clone()));
+                self.stats_by_series.insert(&token.series_id, &stats);
+            }
+        }
+    }
+
+    //record a completed sale of a token of the series
+    pub(crate) fn internal_stats_record_sale(
+        &mut self,
+        series_id: SeriesId,
+        token_id: &TokenId,
+        price: Balance,
+    ) {
+        if let Some(mut series) = self.series_by_id.get(&series_id) {
+            series.volume = Some(series.volume.unwrap_or(0) + price);
+            self.series_by_id.insert(&series_id, &series);
+        }
+
+        let mut stats = self.internal_series_stats(series_id);
+        stats.sales_count += 1;
+        stats.last_sale = Some(LastSale {
+            token_id: token_id.clone(),
+            price: U128(price),
+            timestamp: U64(env::block_timestamp_ms()),
+        });
+        self.stats_by_series.insert(&series_id, &stats);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::*;
+
+//how many earlier metadata versions are kept for each token
+const MAX_METADATA_HISTORY: usize = 10;
+
+#[near_bindgen]
+impl Contract {
+    /// Replace the metadata of a token. Only the series owner can do this, and only if the series allows it.
+    /// `issued_at`, `starts_at` and `copies` are kept from the current metadata and `expires_at` can only be
+    /// extended. The previous metadata is kept in a bounded history, oldest versions are dropped first.
+    #[payable]
+    pub fn nft_update_metadata(
+        &mut self,
+        token_id: TokenId,
+        metadata: TokenMetadata,
+        memo: Option<String>,
+    ) {
+        assert_at_least_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let token = self.token_by_id.get(&token_id).expect("No token");
+        let series = self
+            .series_by_id
+            .get(&token.series_id)
+            .expect("Not a series");
+        require!(
+            env::predecessor_account_id() == series.owner_id,
+            "Only the series owner can update token metadata"
+        );
+        require!(
+            series.mutable_metadata,
+            "Token metadata is immutable for this series"
+        );
+
+        let previous_metadata = self.token_metadata_by_id.get(&token_id).unwrap();
+
+        //holders keep what they were given: the validity window and edition count stay as they are,
+        //the expiry can only be pushed back or removed
+        let mut metadata = metadata;
+        metadata.issued_at = previous_metadata.issued_at.clone();
+        metadata.starts_at = previous_metadata.starts_at.clone();
+        metadata.copies = previous_metadata.copies;
+        metadata.updated_at = Some(env::block_timestamp_ms().to_string());
+        metadata.assert_valid();
+        if let Some(previous_expires_at) = previous_metadata.expires_at_ms() {
+            require!(
+                metadata
+                    .expires_at_ms()
+                    .is_none_or(|expires_at| expires_at >= previous_expires_at),
+                "expires_at can only be extended"
+            );
+        } else {
+            require!(
+                metadata.expires_at.is_none(),
+                "expires_at can't be added to a token that never expires"
+            );
+        }
+
+        //keep the previous version, dropping the oldest ones once the history is full
+        let mut history = self
+            .token_metadata_history
+            .get(&token_id)
+            .unwrap_or_default();
+        history.push(previous_metadata);
+        if history.len() > MAX_METADATA_HISTORY {
+            history.remove(0);
+        }
+        self.token_metadata_history.insert(&token_id, &history);
+
+        self.token_metadata_by_id.insert(&token_id, &metadata);
+
+        let nft_metadata_update_log: EventLog = EventLog {
+            // Standard name ("nep171").
+            standard: NFT_STANDARD_NAME.to_string(),
+            // Version of the standard ("nft-1.0.0").
+            version: NFT_METADATA_SPEC.to_string(),
+            // The data related with the event stored in a vector.
+            event: EventLogVariant::NftMetadataUpdate(vec![NftMetadataUpdateLog {
+                // A vector containing the token IDs as strings.
+                token_ids: vec![token_id],
+                // An optional memo to include.
+                memo,
+            }]),
+        };
+
+        // Log the serialized json.
+        env::log_str(&nft_metadata_update_log.to_string());
+
+        //the new metadata can be smaller than the old one, only charge for growth
+        let required_storage_in_bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+        refund_deposit(required_storage_in_bytes);
+    }
+
+    //get the earlier metadata versions of a token, oldest first
+    pub fn nft_metadata_history(&self, token_id: TokenId) -> Vec<TokenMetadata> {
+        self.token_metadata_history
+            .get(&token_id)
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,77 @@
+use crate::*;
+
+//the maximum number of tokens that can be swept in a single call (this is limited by GAS)
+const MAX_SWEEP: usize = 50;
+
+#[near_bindgen]
+impl Contract {
+    //whether the token exists and the current time is within its starts_at / expires_at window
+    pub fn nft_is_valid(&self, token_id: TokenId) -> bool {
+        let now_ms = env::block_timestamp_ms();
+        self.token_metadata_by_id
+            .get(&token_id)
+            .is_some_and(|metadata| metadata.has_started(now_ms) && !metadata.is_expired(now_ms))
+    }
+
+    /// Burn expired tokens from series that enforce expiry. Anyone can call this; tokens that aren't
+    /// expired or whose series doesn't enforce expiry are skipped. The freed storage is refunded to the owners.
+    /// Returns the IDs of the tokens that were burned.
+    pub fn sweep_expired_tokens(&mut self, token_ids: Vec<TokenId>) -> Vec<TokenId> {
+        assert!(
+            token_ids.len() <= MAX_SWEEP,
+            "Cannot sweep more than {} tokens at once",
+            MAX_SWEEP
+        );
+
+        let mut burn_logs: Vec<NftBurnLog> = Vec::new();
+        let mut swept = Vec::new();
+
+        for token_id in token_ids {
+            let token = if let Some(token) = self.token_by_id.get(&token_id) {
+                token
+            } else {
+                continue;
+            };
+            let expiry_enforced = self
+                .series_by_id
+                .get(&token.series_id)
+                .is_some_and(|series| series.expiry_enforced);
+            if !expiry_enforced || !self.internal_is_expired(&token_id) {
+                continue;
+            }
+
+            //refund the freed storage to the owner
+            let (token, initial_storage_usage) = self.internal_burn(&token_id);
+            refund_freed_storage(token.owner_id.clone(), initial_storage_usage);
+
+            let owner_id = token.owner_id.to_string();
+            if let Some(log) = burn_logs.iter_mut().find(|log| log.owner_id == owner_id) {
+                log.token_ids.push(token_id.clone());
+            } else {
+                burn_logs.push(NftBurnLog {
+                    owner_id,
+                    authorized_id: Some(env::predecessor_account_id().to_string()),
+                    token_ids: vec![token_id.clone()],
+                    memo: Some("expired".to_string()),
+                });
+            }
+            swept.push(token_id);
+        }
+
+        if !burn_logs.is_empty() {
+            let nft_burn_log: EventLog = EventLog {
+                // Standard name ("nep171").
+                standard: NFT_STANDARD_NAME.to_string(),
+                // Version of the standard ("nft-1.0.0").
+                version: NFT_METADATA_SPEC.to_string(),
+                // One entry per owner, each with the token IDs that were swept.
+                event: EventLogVariant::NftBurn(burn_logs),
+            };
+
+            // Log the serialized json.
+            env::log_str(&nft_burn_log.to_string());
+        }
+
+        swept
+    }
+}